@@ -0,0 +1,100 @@
+//! The rogue-key attack on plain BLS multi-signatures, for demonstration.
+//!
+//! An attacker who announces its key after seeing the honest keys pk_1..pk_n publishes
+//! pk* = x·g2 − Σ pk_i. Summing the n + 1 keys yields x·g2, so H(m)^x is a valid
+//! `nopop_ms` multisignature of all n + 1 signers on any m, although no honest signer took
+//! part. The same rogue key gets nowhere against `bdn_ms`, whose coefficients differ per
+//! key. Against `our_ms` a single rogue key fails too, since cancelling K1 leaves the
+//! weighted key K2 unknown, but OUR-MS is not immune to rogue keys: two colluding keys can
+//! fix both K1 and K2, and then forge at any ell unless the challenge binds the signers'
+//! keys, as `ChallengeContext::for_key` does (see `forgery_lab`).
+
+use mcore::bls12381::bls::bls_hash_to_point;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::pair;
+
+use crate::our_ms::{ChallengeContext, ChallengeLength, OurMultiSignature};
+use crate::types::{MultiSignature, PublicKey, SecretKey};
+
+/// The rogue key x·g2 − Σ `honest`, whose sum with the honest keys is x·g2.
+pub fn rogue_key(x: &SecretKey, honest: &[PublicKey]) -> PublicKey {
+    let mut pk = pair::g2mul(&ECP2::generator(), &x.0);
+
+    for pk_ in honest {
+        pk.sub(&pk_.0);
+    }
+
+    PublicKey(pk)
+}
+
+/// H(m)^x, which `nopop_ms::verify` accepts on m under the plain sum of the honest keys
+/// and the rogue key.
+pub fn forge_nopop(x: &SecretKey, m: &[u8]) -> MultiSignature {
+    MultiSignature(pair::g1mul(&bls_hash_to_point(m), &x.0))
+}
+
+/// The same forgery against `bdn_ms`, which rejects it because the aggregate key is no
+/// longer x·g2.
+pub fn forge_bdn(x: &SecretKey, m: &[u8]) -> MultiSignature {
+    forge_nopop(x, m)
+}
+
+/// The analogous forgery against `our_ms` with the rogue key appended last, so that
+/// K1 = x·g2: S2 = x·H(m) and S1 = c·x·H(m) balance the K1 pairing, but e(H(m), K2)
+/// would need the discrete log of K2 and the check fails. A second rogue key would fix K2
+/// as well; `forgery_lab` runs that attack.
+pub fn forge_our(x: &SecretKey, m: &[u8], ell: ChallengeLength, ctx: &ChallengeContext) -> OurMultiSignature {
+    let s2 = pair::g1mul(&bls_hash_to_point(m), &x.0);
+    let c = ctx.challenge(ell, &s2, m);
+    let s1 = pair::g1mul(&s2, &c);
+
+    OurMultiSignature { s1, s2, ell }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::utils::test_rng;
+    use crate::{bdn_ms, nopop_ms, our_ms};
+
+    const M: &[u8] = b"transfer everything to the attacker";
+
+    //three honest keys and the attacker's rogue key, announced last
+    fn setup() -> (SecretKey, Vec<PublicKey>) {
+        let mut rng = test_rng(14);
+        let mut pk: Vec<PublicKey> = (0..3).map(|_| nopop_ms::keygen(&mut rng).unwrap().1).collect();
+        let (x, _) = nopop_ms::keygen(&mut rng).unwrap();
+        pk.push(rogue_key(&x, &pk));
+
+        (x, pk)
+    }
+
+    #[test]
+    fn nopop_accepts_forgery() {
+        let (x, pk) = setup();
+        let apk = nopop_ms::aggregate_public_keys(&pk).unwrap();
+
+        assert_eq!(nopop_ms::verify(&forge_nopop(&x, M), M, &apk), Ok(()));
+    }
+
+    #[test]
+    fn bdn_rejects_forgery() {
+        let (x, pk) = setup();
+        let apk = bdn_ms::aggregate_public_keys(&pk).unwrap();
+
+        assert_eq!(bdn_ms::verify(&forge_bdn(&x, M), M, &apk), Err(Error::VerificationFailed));
+    }
+
+    #[test]
+    fn our_rejects_forgery() {
+        let (x, pk) = setup();
+        let apk = our_ms::aggregate_public_keys(&pk).unwrap();
+        let ell = ChallengeLength::new(128).unwrap();
+
+        for ctx in [ChallengeContext::for_key(&apk), ChallengeContext::unbound()] {
+            let forgery = forge_our(&x, M, ell, &ctx);
+            assert_eq!(our_ms::verify(&forgery, M, &apk, ell, &ctx), Err(Error::VerificationFailed));
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! The BDN multi-signature scheme (Boneh, Drijvers and Neven, 2018).
+//!
+//! Each signer scales its key by a coefficient derived from its own public key and the
+//! whole signer set, which defeats rogue-key attacks at the cost of a G2 multi-scalar
+//! multiplication during public-key aggregation.
+//!
+//! The signer set is hashed once into a [`SignerSetDigest`]; each coefficient is then
+//! derived from that digest and a single public key, so deriving all n coefficients is
+//! linear in n. Both hashes are `expand_message_xmd` with SHA-256 (RFC 9380) under
+//! separate domain-separation tags, and coefficients are 128-bit as in BDN18.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::bls::bls_hash_to_point;
+use mcore::bls12381::ecp::ECP;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::pair;
+use mcore::bls12381::rom;
+use mcore::hmac;
+use mcore::rand::RAND;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::error::{Error, Result};
+use crate::msm::g2_msm;
+use crate::partial::check_partials;
+use crate::types::{AggregatePublicKey, MultiSignature, PartialSignature, PublicKey, SecretKey};
+use crate::utils::bls_setup;
+#[cfg(feature = "parallel")]
+use crate::utils::chunk_len;
+use crate::{BGS, G2S};
+
+/// Domain-separation tag for hashing the signer set.
+pub const SIGNER_SET_DST: &[u8] = b"BLS_INCSIGN_BDN_BLS12381G2_XMD:SHA-256_SIGNER_SET_";
+/// Domain-separation tag for deriving a signer's coefficient.
+pub const COEFFICIENT_DST: &[u8] = b"BLS_INCSIGN_BDN_BLS12381G2_XMD:SHA-256_COEFFICIENT_";
+/// Coefficient length in bytes.
+const COEFFICIENT_BYTES: usize = 16;
+
+/// A hash of the whole signer set, shared by every coefficient derivation.
+#[derive(Clone, Copy)]
+pub struct SignerSetDigest([u8; 32]);
+
+/// Hashes the concatenated public keys of the signer set, in signer order.
+pub fn signer_set_digest(pkvec: &[PublicKey]) -> Result<SignerSetDigest> {
+    if pkvec.is_empty() {
+        return Err(Error::EmptySignerSet);
+    }
+
+    let mut digest: [u8; 32] = [0; 32];
+    hmac::xmd_expand(hmac::MC_SHA2, 32, &mut digest, 32, SIGNER_SET_DST, &concat_pks(pkvec));
+
+    Ok(SignerSetDigest(digest))
+}
+
+/// Derives the 128-bit coefficient H(pk, digest) mod r of the signer holding `pk`.
+pub fn coefficient(pk: &PublicKey, digest: &SignerSetDigest) -> BIG {
+    let order = BIG::new_ints(&rom::CURVE_ORDER);
+
+    let mut input: Vec<u8> = Vec::with_capacity(G2S + 32);
+    input.extend(pk.to_bytes());
+    input.extend(digest.0);
+
+    hash_to_coefficient(&input, &order)
+}
+
+/// Generates a signer key pair.
+pub fn keygen(rng: &mut RAND) -> Result<(SecretKey, PublicKey)> {
+    bls_setup(rng)
+}
+
+/// Produces the partial signature of the signer holding (`sk`, `pk`) on `m`, where `pkvec`
+/// is the full signer set. The coefficient-scaled key never leaves this function.
+pub fn sign(sk: &SecretKey, pk: &PublicKey, pkvec: &[PublicKey], m: &[u8]) -> Result<PartialSignature> {
+    sign_with_digest(sk, pk, &signer_set_digest(pkvec)?, m)
+}
+
+/// As [`sign`], with the signer-set digest computed beforehand.
+pub fn sign_with_digest(sk: &SecretKey, pk: &PublicKey, digest: &SignerSetDigest, m: &[u8]) -> Result<PartialSignature> {
+    let order = BIG::new_ints(&rom::CURVE_ORDER);
+    let newsk = BIG::modmul(&sk.0, &coefficient(pk, digest), &order);
+
+    Ok(PartialSignature(pair::g1mul(&bls_hash_to_point(m), &newsk)))
+}
+
+/// Combines partial signatures by summing them.
+pub fn combine(sig: &[PartialSignature]) -> Result<MultiSignature> {
+    if sig.is_empty() {
+        return Err(Error::EmptySignerSet);
+    }
+
+    let mut sigma = ECP::new();
+
+    for s in sig {
+        sigma.add(&s.0);
+    }
+
+    Ok(MultiSignature(sigma))
+}
+
+/// As [`combine`], after checking each partial signature on `m` against the key at the
+/// same position in `pkvec`, scaled by its coefficient. Fails naming the signers whose
+/// partial signatures are invalid.
+pub fn combine_verified(sig: &[PartialSignature], pkvec: &[PublicKey], m: &[u8]) -> Result<MultiSignature> {
+    let digest = signer_set_digest(pkvec)?;
+    let keys: Vec<ECP2> = pkvec.iter().map(|pk| pk.0.clone()).collect();
+    let coefs: Vec<BIG> = pkvec.iter().map(|pk| coefficient(pk, &digest)).collect();
+    check_partials(sig, &keys, &coefs, &bls_hash_to_point(m))?;
+
+    combine(sig)
+}
+
+/// Aggregates public keys as the sum of H(pk_i, PK)·pk_i.
+pub fn aggregate_public_keys(pkvec: &[PublicKey]) -> Result<AggregatePublicKey> {
+    if pkvec.is_empty() {
+        return Err(Error::EmptySignerSet);
+    }
+
+    Ok(AggregatePublicKey(bdn_aggpk(pkvec, &signer_set_digest(pkvec)?)))
+}
+
+/// Checks e(sigma, g2) == e(H(m), apk).
+pub fn verify(sig: &MultiSignature, m: &[u8], apk: &AggregatePublicKey) -> Result<()> {
+    let mut s = sig.0.clone();
+    s.neg();
+
+    let mut r = pair::initmp();
+    pair::another(&mut r, &ECP2::generator(), &s);
+    pair::another(&mut r, &apk.0, &bls_hash_to_point(m));
+
+    let mut v = pair::miller(&mut r);
+    v = pair::fexp(&v);
+
+    if v.isunity() {
+        Ok(())
+    } else {
+        Err(Error::VerificationFailed)
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+fn bdn_aggpk(pkvec: &[PublicKey], digest: &SignerSetDigest) -> ECP2 {
+    let points: Vec<ECP2> = pkvec.iter().map(|pk| pk.0.clone()).collect();
+    let coefs: Vec<BIG> = pkvec.iter().map(|pk| coefficient(pk, digest)).collect();
+
+    g2_msm(&points, &coefs)
+}
+
+//one multi-scalar multiplication per chunk, summed
+#[cfg(feature = "parallel")]
+fn bdn_aggpk(pkvec: &[PublicKey], digest: &SignerSetDigest) -> ECP2 {
+    pkvec
+        .par_chunks(chunk_len(pkvec.len()))
+        .map(|chunk| {
+            let points: Vec<ECP2> = chunk.iter().map(|pk| pk.0.clone()).collect();
+            let coefs: Vec<BIG> = chunk.iter().map(|pk| coefficient(pk, digest)).collect();
+
+            g2_msm(&points, &coefs)
+        })
+        .reduce(ECP2::new, |mut a, b| {
+            a.add(&b);
+            a
+        })
+}
+
+//concatenate all pk bytes
+fn concat_pks(pkvec: &[PublicKey]) -> Vec<u8> {
+    let mut allpk: Vec<u8> = Vec::with_capacity(pkvec.len() * G2S);
+
+    for pk in pkvec {
+        allpk.extend(pk.to_bytes());
+    }
+
+    allpk
+}
+
+fn hash_to_coefficient(input: &[u8], order: &BIG) -> BIG {
+    let mut okm: [u8; COEFFICIENT_BYTES] = [0; COEFFICIENT_BYTES];
+    hmac::xmd_expand(hmac::MC_SHA2, 32, &mut okm, COEFFICIENT_BYTES, COEFFICIENT_DST, input);
+
+    //BIG::frombytes reads BGS bytes, big-endian
+    let mut padded: [u8; BGS] = [0; BGS];
+    padded[BGS - COEFFICIENT_BYTES..].copy_from_slice(&okm);
+
+    let mut coef = BIG::frombytes(&padded);
+    coef.rmod(order);
+
+    coef
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{fromhex, tohex};
+
+    //pk_k = k·g2 for k = 1, 2, 3; digest and coefficients cross-checked against an
+    //independent expand_message_xmd, the aggregate against (Σ k·c_k)·g2
+    const PK: [&str; 3] = [
+        "03024AA2B2F08F0A91260805272DC51051C6E47AD4FA403B02B4510B647AE3D1770BAC0326A805BBEFD48056C8C121BDB813E02B6052719F607DACD3A088274F65596BD0D09920B61AB5DA61BBDC7F5049334CF11213945D57E5AC7D055D042B7E",
+        "031638533957D540A9D2370F17CC7ED5863BC0B995B8825E0EE1EA1E1E4D00DBAE81F14B0BF3611B78C952AACAB827A0530A4EDEF9C1ED7F729F520E47730A124FD70662A904BA1074728114D1031E1572C6C886F6B57EC72A6178288C47C33577",
+        "02122915C824A0857E2EE414A3DCCB23AE691AE54329781315A0C75DF1C04D6D7A50A030FC866F09D516020EF82324AFAE09380275BBC8E5DCEA7DC4DD7E0550FF2AC480905396EDA55062650F8D251C96EB480673937CC6D9D6A44AAA56CA66DC",
+    ];
+    const DIGEST: &str = "D221549566EDFFE84E26E4DA47A15866E58C9061F0ECB3A27A45235F8C9D9A3F";
+    const COEFFICIENTS: [&str; 3] = ["865DE1C429447DCC97105A9985DF79A0", "EC82C68AFF12411AEC914E1B789B877F", "FCAFA160674876343A125FA0783D1212"];
+    const AGGREGATE: &str = "03121AA24E1C9CDEF3EA1C39BC01BE3B4997A0A32DB21B4716F720FDA95ED99AA5BF1309A2E5B5EF9A7D7CE4DA11B851C1150C941676ACEA5C2EFC0D8B4301BCE0E70F0917DBA39944B89F0F664727D7510F47E98713FF71A9BFE1E08662968C8D";
+
+    fn pkvec() -> Vec<PublicKey> {
+        (1..=3)
+            .map(|k| {
+                let mut b: [u8; BGS] = [0; BGS];
+                b[BGS - 1] = k;
+                SecretKey::from_bytes(&b).unwrap().public_key()
+            })
+            .collect()
+    }
+
+    fn big_hex(x: &BIG) -> String {
+        let mut b: [u8; BGS] = [0; BGS];
+        x.tobytes(&mut b);
+        tohex(&b).trim_start_matches('0').to_string()
+    }
+
+    #[test]
+    fn fixed_keys() {
+        let pkvec = pkvec();
+        for (pk, expected) in pkvec.iter().zip(PK) {
+            assert_eq!(tohex(&pk.to_bytes()), expected);
+        }
+    }
+
+    #[test]
+    fn signer_set_digest_known_answer() {
+        assert_eq!(tohex(&signer_set_digest(&pkvec()).unwrap().0), DIGEST);
+    }
+
+    #[test]
+    fn coefficient_known_answer() {
+        let pkvec = pkvec();
+        let digest = signer_set_digest(&pkvec).unwrap();
+
+        for (pk, expected) in pkvec.iter().zip(COEFFICIENTS) {
+            assert_eq!(big_hex(&coefficient(pk, &digest)), expected);
+        }
+    }
+
+    #[test]
+    fn aggregate_public_keys_known_answer() {
+        let apk = aggregate_public_keys(&pkvec()).unwrap();
+        assert_eq!(tohex(&apk.to_bytes()), AGGREGATE);
+        assert_eq!(AggregatePublicKey::from_bytes(&fromhex(AGGREGATE).unwrap()).unwrap().to_bytes(), apk.to_bytes());
+    }
+
+    //four uneven chunks of 3, 3, 3 and 1 keys
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_aggregation_matches_sequential() {
+        use crate::utils::with_threads;
+
+        let pkvec: Vec<PublicKey> = (1..=10)
+            .map(|k| {
+                let mut b: [u8; BGS] = [0; BGS];
+                b[BGS - 1] = k;
+                SecretKey::from_bytes(&b).unwrap().public_key()
+            })
+            .collect();
+        let digest = signer_set_digest(&pkvec).unwrap();
+
+        let points: Vec<ECP2> = pkvec.iter().map(|pk| pk.0.clone()).collect();
+        let coefs: Vec<BIG> = pkvec.iter().map(|pk| coefficient(pk, &digest)).collect();
+
+        assert!(with_threads(4, || bdn_aggpk(&pkvec, &digest)).equals(&g2_msm(&points, &coefs)));
+    }
+
+    #[test]
+    fn digest_depends_on_signer_order() {
+        let mut pkvec = pkvec();
+        pkvec.swap(0, 1);
+
+        assert_ne!(tohex(&signer_set_digest(&pkvec).unwrap().0), DIGEST);
+    }
+}
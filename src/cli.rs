@@ -0,0 +1,224 @@
+//! Non-interactive subcommands operating on hex-encoded key, signature and message files.
+
+use std::collections::HashMap;
+use std::error;
+use std::fs;
+
+use bls_incsign::our_ms::{ChallengeContext, ChallengeLength, OurAggregateKey, OurMultiSignature};
+use bls_incsign::utils::{fromhex, gen_seed, tohex};
+use bls_incsign::{bdn_ms, nopop_ms, our_ms};
+use bls_incsign::{AggregatePublicKey, MultiSignature, PartialSignature, PublicKey, SecretKey};
+use bls_incsign::Error;
+
+type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
+
+pub const USAGE: &str = "Usage:
+  bls_incsign [bench]
+      run the interactive benchmark
+  bls_incsign bdn-scaling [<max signers>]
+      time BDN-MS coefficient derivation and key aggregation for up to 10000 (or the given) signers
+  bls_incsign rogue-key [<honest signers>]
+      forge a multisignature with a rogue key; exits with a non-zero status unless only
+      BLS-MS-noPoP accepts it
+  bls_incsign forgery-lab <ell> [<attempts>]
+      run a rogue-key forgery against OUR-MS with a small ell (at most 32 bits) and compare
+      its success rate with 2^-ell; defaults to 4·2^ell attempts
+  bls_incsign batch-verify [<count>]
+      time verifying 100 (or <count>) OUR-MS multisignatures one by one and as a batch, and
+      find a corrupted one by bisection
+  bls_incsign keygen --out <prefix>
+      write a key pair to <prefix>.sk and <prefix>.pk
+  bls_incsign sign --scheme <our|bdn|nopop> --sk <file> --msg <file> --out <file> [<pk files>...]
+      sign a message file; BDN-MS also takes the public keys of the whole signer set
+  bls_incsign combine --scheme <our|bdn|nopop> --msg <file> --out <file> [--sk <file> --apk <file> --ell <bits> --session <id>] <sig files>...
+      combine partial signatures; OUR-MS needs the first signer's secret key, the aggregated
+      key and ell, and binds its challenge to the message, the aggregated key and, if given,
+      a session id
+  bls_incsign aggregate --scheme <our|bdn|nopop> --out <file> <pk files>...
+      aggregate public keys, in signer order
+  bls_incsign verify --scheme <our|bdn|nopop> --msg <file> --sig <file> --apk <file> [--ell <bits> --session <id>]
+      verify a multisignature; exits with a non-zero status if it does not verify.
+      OUR-MS needs --ell and rejects signatures made with any other ell, and needs the same
+      --session as combine";
+
+#[derive(Clone, Copy)]
+enum Scheme {
+    Our,
+    Bdn,
+    Nopop,
+}
+
+struct Args {
+    options: HashMap<String, String>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Args> {
+        let mut options = HashMap::new();
+        let mut positional = Vec::new();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = iter.next().ok_or(format!("missing value for --{}", name))?;
+                    options.insert(name.to_string(), value.clone());
+                }
+                None => positional.push(arg.clone()),
+            }
+        }
+
+        Ok(Args { options, positional })
+    }
+
+    fn get(&self, name: &str) -> Result<&str> {
+        Ok(self.options.get(name).ok_or(format!("missing --{}", name))?)
+    }
+
+    fn scheme(&self) -> Result<Scheme> {
+        match self.get("scheme")? {
+            "our" => Ok(Scheme::Our),
+            "bdn" => Ok(Scheme::Bdn),
+            "nopop" => Ok(Scheme::Nopop),
+            s => Err(format!("unknown scheme '{}'", s).into()),
+        }
+    }
+
+    fn ell(&self) -> Result<ChallengeLength> {
+        let ell = self.get("ell")?;
+        let bits = ell.parse().map_err(|_| format!("invalid ell '{}'", ell))?;
+
+        Ok(ChallengeLength::new(bits)?)
+    }
+
+    //bound to the message, the aggregated key and, if given, the session id
+    fn challenge_context(&self, apk: &OurAggregateKey) -> ChallengeContext {
+        match self.options.get("session") {
+            Some(id) => ChallengeContext::for_key(apk).bind_session_id(id.as_bytes()),
+            None => ChallengeContext::for_key(apk),
+        }
+    }
+}
+
+/// Runs the subcommand `cmd`. Returns `Ok(false)` if a signature did not verify.
+pub fn run(cmd: &str, args: &[String]) -> Result<bool> {
+    let args = Args::parse(args)?;
+
+    match cmd {
+        "keygen" => keygen(&args),
+        "sign" => sign(&args),
+        "combine" => combine(&args),
+        "aggregate" => aggregate(&args),
+        "verify" => return verify(&args),
+        _ => return Err(format!("unknown command '{}'", cmd).into()),
+    }?;
+
+    Ok(true)
+}
+
+fn keygen(args: &Args) -> Result<()> {
+    let prefix = args.get("out")?;
+    let (sk, pk) = our_ms::keygen(&mut gen_seed())?;
+
+    write_hex(&format!("{}.sk", prefix), &sk.to_bytes())?;
+    write_hex(&format!("{}.pk", prefix), &pk.to_bytes())
+}
+
+fn sign(args: &Args) -> Result<()> {
+    let sk = SecretKey::from_bytes(&read_hex(args.get("sk")?)?)?;
+    let m = read_file(args.get("msg")?)?;
+
+    let sig = match args.scheme()? {
+        Scheme::Our => our_ms::sign(&sk, &m)?,
+        Scheme::Nopop => nopop_ms::sign(&sk, &m)?,
+        Scheme::Bdn => bdn_ms::sign(&sk, &sk.public_key(), &read_pks(&args.positional)?, &m)?,
+    };
+
+    write_hex(args.get("out")?, &sig.to_bytes())
+}
+
+fn combine(args: &Args) -> Result<()> {
+    let sig = args
+        .positional
+        .iter()
+        .map(|path| Ok(PartialSignature::from_bytes(&read_hex(path)?)?))
+        .collect::<Result<Vec<_>>>()?;
+
+    let out = args.get("out")?;
+
+    match args.scheme()? {
+        Scheme::Our => {
+            let sk = SecretKey::from_bytes(&read_hex(args.get("sk")?)?)?;
+            let m = read_file(args.get("msg")?)?;
+            let apk = OurAggregateKey::from_bytes(&read_hex(args.get("apk")?)?)?;
+            let sigma = our_ms::combine(&sig, &sk, &m, args.ell()?, &args.challenge_context(&apk))?;
+            write_hex(out, &sigma.to_bytes())
+        }
+        Scheme::Bdn => write_hex(out, &bdn_ms::combine(&sig)?.to_bytes()),
+        Scheme::Nopop => write_hex(out, &nopop_ms::combine(&sig)?.to_bytes()),
+    }
+}
+
+fn aggregate(args: &Args) -> Result<()> {
+    let pkvec = read_pks(&args.positional)?;
+    let out = args.get("out")?;
+
+    match args.scheme()? {
+        Scheme::Our => write_hex(out, &our_ms::aggregate_public_keys(&pkvec)?.to_bytes()),
+        Scheme::Bdn => write_hex(out, &bdn_ms::aggregate_public_keys(&pkvec)?.to_bytes()),
+        Scheme::Nopop => write_hex(out, &nopop_ms::aggregate_public_keys(&pkvec)?.to_bytes()),
+    }
+}
+
+fn verify(args: &Args) -> Result<bool> {
+    let m = read_file(args.get("msg")?)?;
+    let sig = read_hex(args.get("sig")?)?;
+    let apk = read_hex(args.get("apk")?)?;
+
+    let res = match args.scheme()? {
+        Scheme::Our => {
+            let sigma = OurMultiSignature::from_bytes(&sig)?;
+            let ell = args.ell()?;
+            if sigma.ell() != ell {
+                println!("Signature NOT verified: made with ell = {}, expected {}.", sigma.ell().bits(), ell.bits());
+                return Ok(false);
+            }
+
+            let apk = OurAggregateKey::from_bytes(&apk)?;
+            our_ms::verify(&sigma, &m, &apk, ell, &args.challenge_context(&apk))
+        }
+        Scheme::Bdn => bdn_ms::verify(&MultiSignature::from_bytes(&sig)?, &m, &AggregatePublicKey::from_bytes(&apk)?),
+        Scheme::Nopop => nopop_ms::verify(&MultiSignature::from_bytes(&sig)?, &m, &AggregatePublicKey::from_bytes(&apk)?),
+    };
+
+    match res {
+        Ok(()) => {
+            println!("Signature verified.");
+            Ok(true)
+        }
+        Err(Error::VerificationFailed) => {
+            println!("Signature NOT verified.");
+            Ok(false)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn read_pks(paths: &[String]) -> Result<Vec<PublicKey>> {
+    paths.iter().map(|path| Ok(PublicKey::from_bytes(&read_hex(path)?)?)).collect()
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>> {
+    Ok(fs::read(path).map_err(|e| format!("cannot read {}: {}", path, e))?)
+}
+
+fn read_hex(path: &str) -> Result<Vec<u8>> {
+    let text = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+
+    Ok(fromhex(&text).ok_or(format!("{} is not valid hex", path))?)
+}
+
+fn write_hex(path: &str, bytes: &[u8]) -> Result<()> {
+    Ok(fs::write(path, tohex(bytes) + "\n").map_err(|e| format!("cannot write {}: {}", path, e))?)
+}
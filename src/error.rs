@@ -0,0 +1,59 @@
+//! The crate-wide error type.
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Bytes of the wrong length, or not the encoding of a curve point.
+    InvalidPointEncoding,
+    /// A point lies outside the prime-order subgroup.
+    PointNotInSubgroup,
+    /// A point is the identity, which no honest key or signature is.
+    IdentityPoint,
+    /// Bytes of the wrong length, or a scalar that is zero or not below the group order.
+    InvalidSecretKey,
+    /// The OUR-MS challenge length `ell` is not supported, or not the one the verifier expects.
+    InvalidEll,
+    /// An operation was given no signers, public keys or partial signatures.
+    EmptySignerSet,
+    /// A signer set contains the same public key twice.
+    DuplicateKey,
+    /// A signer index is out of range, repeated, or out of order.
+    InvalidSignerIndex,
+    /// Partial signatures come from keys outside the signer set, or do not cover all of it.
+    SignerSetMismatch,
+    /// The signature does not verify.
+    VerificationFailed,
+    /// The partial signatures of these signers (1-based indices) do not verify.
+    InvalidPartialSignatures(Vec<usize>),
+    /// MIRACL failed to derive a key pair.
+    KeyGenerationFailed,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            Error::InvalidPointEncoding => "invalid point encoding",
+            Error::PointNotInSubgroup => "point not in the prime-order subgroup",
+            Error::IdentityPoint => "point at infinity",
+            Error::InvalidSecretKey => "invalid secret key",
+            Error::InvalidEll => "invalid challenge length ell",
+            Error::EmptySignerSet => "empty signer set",
+            Error::DuplicateKey => "duplicate public key in signer set",
+            Error::InvalidSignerIndex => "invalid signer index",
+            Error::SignerSetMismatch => "partial signatures do not match the signer set",
+            Error::VerificationFailed => "signature verification failed",
+            Error::InvalidPartialSignatures(signers) => {
+                let list: Vec<String> = signers.iter().map(|i| i.to_string()).collect();
+                return write!(f, "invalid partial signatures from signers {}", list.join(", "));
+            }
+            Error::KeyGenerationFailed => "key generation failed",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for Error {}
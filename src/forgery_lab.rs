@@ -0,0 +1,225 @@
+//! Measures how often a generic rogue-key forgery against OUR-MS succeeds for small ell.
+//!
+//! Two colluding signers join the honest signers pk_1..pk_h at the last two positions. For
+//! a guess g of the challenge they pick keys Y1, Y2 with K1 = z·g2 for a z they know and
+//! K2 + g·K1 = 0, which needs no discrete logs:
+//!
+//! Y2 = (n − 1)·A − B − (n − 1 + g)·z·g2 and Y1 = z·g2 − A − Y2,
+//!
+//! where A and B are K1 and K2 of the honest signers and n = h + 2. Then S2 = t·H(m) and
+//! S1 = (z − 1)·S2 verify exactly when c = g. If the challenge context binds the aggregated
+//! key, as `ChallengeContext::for_key` does, c is fresh for every pair of keys and each
+//! attempt succeeds with probability 2^-ell; with `ChallengeContext::unbound` c is known
+//! before the keys are chosen and every attempt succeeds.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::bls::bls_hash_to_point;
+use mcore::bls12381::ecp::ECP;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::pair;
+use mcore::bls12381::rom;
+use mcore::rand::RAND;
+
+use crate::error::{Error, Result};
+use crate::our_ms::{self, ChallengeContext, ChallengeLength, OurMultiSignature};
+use crate::types::PublicKey;
+use crate::BGS;
+
+/// Largest ell the lab accepts; beyond it no attempt count could show a success.
+pub const MAX_LAB_ELL: usize = 32;
+
+/// Outcome of [`run`].
+#[derive(Clone, Copy, Debug)]
+pub struct LabReport {
+    pub ell: ChallengeLength,
+    pub bind_key: bool,
+    pub attempts: u64,
+    /// Attempts whose forgery `our_ms::verify` accepted.
+    pub successes: u64,
+}
+
+impl LabReport {
+    /// Observed success rate per attempt.
+    pub fn success_rate(&self) -> f64 {
+        self.successes as f64 / self.attempts as f64
+    }
+
+    /// Theoretical success probability per attempt: 2^-ell with the aggregated key bound, 1
+    /// without.
+    pub fn bound(&self) -> f64 {
+        if self.bind_key {
+            0.5f64.powi(self.ell.bits() as i32)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Runs `attempts` forgery attempts on `m` against the `honest` signers, with the challenge
+/// bound to the aggregated key if `bind_key` and unbound otherwise.
+pub fn run(
+    honest: &[PublicKey],
+    m: &[u8],
+    ell: ChallengeLength,
+    attempts: u64,
+    bind_key: bool,
+    rng: &mut RAND,
+) -> Result<LabReport> {
+    if ell.bits() > MAX_LAB_ELL {
+        return Err(Error::InvalidEll);
+    }
+    if honest.is_empty() {
+        return Err(Error::EmptySignerSet);
+    }
+
+    let order = BIG::new_ints(&rom::CURVE_ORDER);
+    let hash_m = bls_hash_to_point(m);
+    let span = 1u64 << ell.bits();
+    let n = honest.len() + 2;
+
+    //A and B
+    let honest_apk = our_ms::aggregate_public_keys(honest)?;
+    let (a, b) = (honest_apk.k1, honest_apk.k2);
+
+    //(n − 1)·A − B
+    let mut base = pair::g2mul(&a, &BIG::new_int((n - 1) as isize));
+    base.sub(&b);
+
+    let z = BIG::randomnum(&order, rng);
+    let zg = pair::g2mul(&ECP2::generator(), &z);
+    let z_minus_one = BIG::modadd(&z, &BIG::modneg(&BIG::new_int(1), &order), &order);
+
+    let mut pkvec: Vec<PublicKey> = honest.to_vec();
+    let mut forgery = new_forgery(&hash_m, &z_minus_one, ell, &order, rng);
+    let mut y2 = ECP2::new();
+    let mut successes = 0;
+
+    for k in 0..attempts {
+        let guess = if bind_key {
+            //the keys for a guess repeat every span attempts; a fresh S2 keeps c fresh
+            if k > 0 && k % span == 0 {
+                forgery = new_forgery(&hash_m, &z_minus_one, ell, &order, rng);
+            }
+            k % span
+        } else {
+            //c does not depend on the keys: read it off and use a fresh S2 each time
+            forgery = new_forgery(&hash_m, &z_minus_one, ell, &order, rng);
+            small_to_u64(&ChallengeContext::unbound().challenge(ell, &forgery.s2, m))
+        };
+
+        //Y2 steps down by z·g2 per guess; recomputed whenever the guess is not the successor
+        if k == 0 || guess == 0 || !bind_key {
+            y2 = pair::g2mul(&zg, &BIG::new_int((n - 1) as isize));
+            y2.add(&pair::g2mul(&zg, &BIG::new_int(guess as isize)));
+            y2.neg();
+            y2.add(&base);
+        } else {
+            y2.sub(&zg);
+        }
+
+        let mut y1 = zg.clone();
+        y1.sub(&a);
+        y1.sub(&y2);
+
+        pkvec.truncate(honest.len());
+        pkvec.push(PublicKey(y1));
+        pkvec.push(PublicKey(y2.clone()));
+
+        let apk = our_ms::aggregate_public_keys(&pkvec)?;
+        let ctx = if bind_key {
+            ChallengeContext::for_key(&apk)
+        } else {
+            ChallengeContext::unbound()
+        };
+
+        //any c other than the guess leaves e(H(m), (c − g)·K1) != 1, so skip the pairings
+        if small_to_u64(&ctx.challenge(ell, &forgery.s2, m)) != guess {
+            continue;
+        }
+
+        if our_ms::verify(&forgery, m, &apk, ell, &ctx).is_ok() {
+            successes += 1;
+        }
+    }
+
+    Ok(LabReport {
+        ell,
+        bind_key,
+        attempts,
+        successes,
+    })
+}
+
+//S2 = t·H(m) for a random t, S1 = (z − 1)·S2
+fn new_forgery(
+    hash_m: &ECP,
+    z_minus_one: &BIG,
+    ell: ChallengeLength,
+    order: &BIG,
+    rng: &mut RAND,
+) -> OurMultiSignature {
+    let s2 = pair::g1mul(hash_m, &BIG::randomnum(order, rng));
+    let s1 = pair::g1mul(&s2, z_minus_one);
+
+    OurMultiSignature { s1, s2, ell }
+}
+
+//c has at most MAX_LAB_ELL bits
+fn small_to_u64(c: &BIG) -> u64 {
+    let mut bytes: [u8; BGS] = [0; BGS];
+    c.tobytes(&mut bytes);
+
+    let mut low: [u8; 8] = [0; 8];
+    low.copy_from_slice(&bytes[BGS - 8..]);
+
+    u64::from_be_bytes(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_rng;
+
+    const M: &[u8] = b"forgery lab";
+
+    fn honest(rng: &mut RAND) -> Vec<PublicKey> {
+        (0..3).map(|_| our_ms::keygen(rng).unwrap().1).collect()
+    }
+
+    fn lab(bits: usize, attempts: u64, bind_key: bool) -> Result<LabReport> {
+        let mut rng = test_rng(15);
+        let honest = honest(&mut rng);
+
+        run(&honest, M, ChallengeLength::new(bits)?, attempts, bind_key, &mut rng)
+    }
+
+    #[test]
+    fn unbound_challenge_always_forges() {
+        for bits in [1, MAX_LAB_ELL] {
+            let report = lab(bits, 4, false).unwrap();
+            assert_eq!(report.successes, report.attempts);
+            assert_eq!(report.bound(), 1.0);
+        }
+    }
+
+    #[test]
+    fn bound_challenge_forges_at_about_the_bound() {
+        //ell = 0 leaves c = 0 whatever is hashed
+        let report = lab(0, 8, true).unwrap();
+        assert_eq!(report.successes, 8);
+        assert_eq!(report.bound(), 1.0);
+
+        //64 attempts at 2^-1: 32 expected, outside 16..=48 with probability below 2^-14
+        let report = lab(1, 64, true).unwrap();
+        assert_eq!(report.bound(), 0.5);
+        assert!((16..=48).contains(&report.successes), "{} successes", report.successes);
+    }
+
+    #[test]
+    fn rejects_large_ell_and_no_honest_signers() {
+        assert!(matches!(lab(MAX_LAB_ELL + 1, 1, true), Err(Error::InvalidEll)));
+
+        let mut rng = test_rng(15);
+        assert!(matches!(run(&[], M, ChallengeLength::new(4).unwrap(), 1, true, &mut rng), Err(Error::EmptySignerSet)));
+    }
+}
@@ -0,0 +1,43 @@
+//! BLS multi-signature schemes built on the MIRACL Core BLS12-381 implementation.
+//!
+//! - [`our_ms`]: the fork-free BLS multi-signature scheme with incremental signing
+//! - [`our_subset`]: OUR-MS signed by any subset of a registered committee
+//! - [`our_aggregate`]: an OUR-MS-style incremental aggregate signature, one message per signer
+//! - [`bdn_ms`]: the BDN multi-signature scheme (BDN18), used as a baseline
+//! - [`pop_ms`]: plain BLS multi-signature with proof-of-possession, used as a baseline
+//! - [`nopop_ms`]: plain BLS multi-signature without proof-of-possession (insecure, baseline only)
+//!
+//! [`attack`] demonstrates the rogue-key attack that breaks [`nopop_ms`] and fails against
+//! the other schemes, and [`forgery_lab`] measures how small an OUR-MS challenge can get.
+
+extern crate mcore;
+
+use mcore::bls12381::bls;
+
+pub mod error;
+mod msm;
+mod partial;
+pub mod types;
+pub mod utils;
+pub mod nopop_ms;
+pub mod pop_ms;
+pub mod bdn_ms;
+pub mod signer_set;
+pub mod our_ms;
+pub mod our_subset;
+pub mod our_aggregate;
+pub mod our_ranges;
+pub mod our_keystate;
+pub mod our_batch;
+pub mod our_prepared;
+pub mod attack;
+pub mod forgery_lab;
+
+pub use error::{Error, Result};
+pub use types::{AggregatePublicKey, MultiSignature, PartialSignature, PublicKey, SecretKey};
+
+//field sizes
+pub const BFS: usize = bls::BFS;
+pub const BGS: usize = bls::BGS;
+pub const G1S: usize = BFS + 1; /* Group 1 Size  - compressed */
+pub const G2S: usize = 2 * BFS + 1; /* Group 2 Size  - compressed */
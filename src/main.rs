@@ -1,680 +1,740 @@
-extern crate mcore;
-
-use mcore::bls12381::bls::bls_hash_to_point;
-use mcore::bls12381::ecp2::ECP2;
-use mcore::bls12381::ecp::ECP;
-use mcore::bls12381::big::BIG;
-use mcore::hash256::HASH256;
+extern crate bls_incsign;
+
+mod cli;
+
+use bls_incsign::our_ms::{ChallengeContext, ChallengeLength};
+use bls_incsign::our_aggregate::SignedMessage;
+use bls_incsign::our_batch::BatchItem;
+use bls_incsign::our_keystate::AggregateKeyState;
+use bls_incsign::our_prepared::PreparedVerifyingKey;
+use bls_incsign::our_subset::Participation;
+use bls_incsign::pop_ms::ProofOfPossession;
+use bls_incsign::signer_set::SignerSet;
+use bls_incsign::utils::{fmt_time, gen_seed};
+use bls_incsign::{attack, bdn_ms, forgery_lab, nopop_ms, our_aggregate, our_batch, our_ms, our_subset, pop_ms, PartialSignature, PublicKey, Result, SecretKey, G1S, G2S};
 use mcore::rand::RAND;
-use rand::{rngs::OsRng, RngCore};
-//use std::io::Read;
-use std::time::Instant;
-use mcore::bls12381::bls;
-use mcore::bls12381::rom;
-use mcore::bls12381::pair;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::env;
 use std::io::{self, Write};
+use std::process;
+use std::str::FromStr;
+use std::time::Instant;
 
+#[derive(Default)]
+struct Timings {
+    setup: u128,
+    sign: u128,
+    combine: u128,
+    pkagg: u128,
+    verify: u128,
+    //scheme-specific measurements, reported after the common phases
+    extra: Vec<(&'static str, u128)>,
+}
 
-//field sizes
-const BFS: usize = bls::BFS;
-const BGS: usize = bls::BGS;
-const G1S: usize = BFS + 1; /* Group 1 Size  - compressed */
-const G2S: usize = 2 * BFS +1 ; /* Group 2 Size  - compressed */
+fn printbinary(array: &[u8]) {
+    for b in array {
+        print!("{:02X}", b)
+    }
+    println!()
+}
 
-pub fn printbinary(array: &[u8]) {
-    for i in 0..array.len() {
-        print!("{:02X}", array[i])
+fn print_verify(ok: bool, time_vrf: u128) {
+    if ok {
+        println!("Signature verified.");
+    } else {
+        println!("Signature NOT verified.");
     }
-    println!("")
+    println!("Verify time taken: {}ns\n", fmt_time(&time_vrf));
 }
 
-pub fn bigtobytes(num: &BIG) -> [u8; BGS]{
-    let mut n: [u8; BGS] = [0; BGS];
-    num.tobytes(&mut n);
+//a key pair for every signer, and the time it took; on all cores with `parallel`
+fn keygen_all(rng: &mut [RAND], keygen: fn(&mut RAND) -> Result<(SecretKey, PublicKey)>) -> Result<(Vec<SecretKey>, Vec<PublicKey>, u128)> {
+    let time = Instant::now();
 
-    return n;    
-}
+    #[cfg(feature = "parallel")]
+    let pairs: Vec<(SecretKey, PublicKey)> = rng.par_iter_mut().map(keygen).collect::<Result<_>>()?;
+    #[cfg(not(feature = "parallel"))]
+    let pairs: Vec<(SecretKey, PublicKey)> = rng.iter_mut().map(keygen).collect::<Result<_>>()?;
 
-pub fn ecptobytes(n: &ECP) -> [u8; G1S]{
-    let mut point: [u8; G1S] = [0; G1S];
-    n.tobytes(&mut point, true);
+    let time_setup = time.elapsed().as_nanos();
+    let (sk, pk) = pairs.into_iter().unzip();
 
-    return point;    
+    Ok((sk, pk, time_setup))
 }
 
-pub fn ecp2tobytes(n: &ECP2) -> [u8; G2S]{
-    let mut point: [u8; G2S] = [0; G2S];
-    n.tobytes(&mut point, true);
-
-    return point;    
+//f(i) for every signer i, in signer order; on all cores with `parallel`
+fn map_signers<T: Send>(l: usize, f: impl Fn(usize) -> Result<T> + Sync + Send) -> Result<Vec<T>> {
+    #[cfg(feature = "parallel")]
+    return (0..l).into_par_iter().map(f).collect();
+    #[cfg(not(feature = "parallel"))]
+    return (0..l).map(f).collect();
 }
 
-fn bls_setup(rng: &mut RAND, mut sk: &mut [u8], mut pk: &mut [u8]) -> isize{
-  
-    let mut ikm: [u8; 32] = [0; 32];
+fn blsms_nopop(rng: &mut [RAND], benchmark: bool) -> Result<Timings> {
+    let l = rng.len();
+    let mut time;
+
+    //setup
+    let (sk, pk, time_setup) = keygen_all(rng, nopop_ms::keygen)?;
 
-    for i in 0..32 {
-        ikm[i]=rng.getbyte();
+    if !benchmark {
+        println!("{} BLS Setup OK, completed in {}ns", l, fmt_time(&time_setup));
     }
 
-    return bls::key_pair_generate(&ikm, &mut sk, &mut pk)    
-}
+    //sign
+    let m: &str = "test message";
 
-/* 
-fn bls(rng: &mut RAND) {    
-    //sk
-    let mut sk: [u8; BGS] = [0; BGS];
+    time = Instant::now();
+    let sig: Vec<PartialSignature> = map_signers(l, |i| nopop_ms::sign(&sk[i], m.as_bytes()))?;
+    let time_sign = time.elapsed().as_nanos();
 
-    //pk
-    let mut pk: [u8; G2S] = [0; G2S];
+    if !benchmark {
+        println!("All {} signers signed in: {}ns\n", l, fmt_time(&time_sign));
+    }
 
-    let mut time = Instant::now();
-    let mut res = bls_setup(rng, &mut sk, &mut pk);
-    let time_setup = time.elapsed().as_nanos();
+    time = Instant::now();
+    let sigma = nopop_ms::combine(&sig)?;
+    let time_comb = time.elapsed().as_nanos();
+
+    //combining again, checking every partial signature first
+    time = Instant::now();
+    nopop_ms::combine_verified(&sig, &pk, m.as_bytes())?;
+    let time_comb_vrf = time.elapsed().as_nanos();
 
-    if res ==0 {
-        println!("BLS Setup OK, completed in {}ns", fmt_time(&time_setup));
-    }else{
-        println!("BLS Setup FAILED, completed in {}ns", fmt_time(&time_setup));
+    if !benchmark {
+        print!("\nSignature : 0x");
+        printbinary(&sigma.to_bytes());
+        println!("Combining time taken: {}ns", fmt_time(&time_comb));
+        println!("Combining time taken with partial signature checks: {}ns\n", fmt_time(&time_comb_vrf));
     }
-    println!("subgroup order bits: {}", BGS*8);
-    println!("group modulus bits : {}", G1S*8);
-    print!("Private key : 0x");
-    printbinary(&sk);
-    print!("Public  key : 0x");
-    printbinary(&pk);    
 
-    //sign
-    let m: &str = "test message";
-    let mut sig: [u8; G1S] = [0; G1S];
-    
+    //pk aggregation
     time = Instant::now();
-    bls::core_sign(&mut sig, &m.as_bytes(), &sk);
-    let time_sign = time.elapsed().as_nanos();
+    let apk = nopop_ms::aggregate_public_keys(&pk)?;
+    let time_apk = time.elapsed().as_nanos();
 
-    print!("\nSignature : 0x");
-    printbinary(&sig);
-    println!("Sign time taken: {}ns\n", fmt_time(&time_sign));
+    if !benchmark {
+        print!("APK : 0x");
+        printbinary(&apk.to_bytes());
+        println!("AggPK time taken: {}ns\n", fmt_time(&time_apk));
+    }
 
     //verify
     time = Instant::now();
-    res = bls::core_verify(&sig, &m.as_bytes(), &pk);
+    let ok = nopop_ms::verify(&sigma, m.as_bytes(), &apk).is_ok();
     let time_vrf = time.elapsed().as_nanos();
-    if res == 0 {
-        println!("Signature verified.");
-        println!("Verify time taken: {}ns\n", fmt_time(&time_vrf));
-    } else {
-        println!("Signature NOT verified.");
-        println!("Verify time taken: {}ns\n", fmt_time(&time_vrf));
+
+    if !benchmark {
+        print_verify(ok, time_vrf);
     }
+
+    Ok(Timings {
+        setup: time_setup,
+        sign: time_sign,
+        combine: time_comb,
+        pkagg: time_apk,
+        verify: time_vrf,
+        extra: vec![("Combine (verified)", time_comb_vrf)],
+    })
 }
- */
 
- fn blsms_nopop(rng: &mut [RAND], benchmark: bool) -> (u128, u128, u128, u128, u128){    
+fn blsms_pop(rng: &mut [RAND], benchmark: bool) -> Result<Timings> {
     let l = rng.len();
-    let mut sk: Vec<[u8; BGS]> = vec![[0; BGS]; l];
-    let mut pk: Vec<[u8; G2S]> = vec![[0; G2S]; l];
-    let mut sig: Vec<[u8; G1S]> = vec![[0; G1S]; l];
+    let mut sk: Vec<SecretKey> = Vec::with_capacity(l);
+    let mut pk: Vec<PublicKey> = Vec::with_capacity(l);
+    let mut proof: Vec<ProofOfPossession> = Vec::with_capacity(l);
 
-    let mut res: isize = 0;
     let mut time;
     let mut time_setup: u128 = 0;
+    let mut time_prove: u128 = 0;
 
-    //setup
-    for i in 0..l{
-        //sk
-        sk[i] = [0; BGS];
-
-        //pk
-        pk[i] = [0; G2S];
-
+    //setup, each signer also proving possession of its key
+    for r in rng.iter_mut() {
         time = Instant::now();
-        res += bls_setup(&mut rng[i], &mut sk[i], &mut pk[i]);
+        let (sk_, pk_) = pop_ms::keygen(r)?;
         time_setup += time.elapsed().as_nanos();
+
+        time = Instant::now();
+        proof.push(pop_ms::pop_prove(&sk_, &pk_));
+        time_prove += time.elapsed().as_nanos();
+
+        sk.push(sk_);
+        pk.push(pk_);
     }
 
-    /* check complexity difference
-    time = Instant::now();
-    pair::g1mul(&ECP::generator(), &BIG::frombytes(&sk[0]));
-    let mut ttime = time.elapsed().as_nanos();
-    println!("G1 mult. takes: {}ns", fmt_time(&ttime));
+    if !benchmark {
+        println!("{} BLS Setup OK, completed in {}ns", l, fmt_time(&time_setup));
+        println!("{} PoPs generated in {}ns", l, fmt_time(&time_prove));
+    }
 
+    //one-off check of every proof, before the keys are ever aggregated
     time = Instant::now();
-    pair::g2mul(&ECP2::generator(), &BIG::frombytes(&sk[0]));
-    ttime = time.elapsed().as_nanos();
-    println!("G2 mult. takes: {}ns", fmt_time(&ttime));
-    */
+    let pop_ok = pk.iter().zip(proof.iter()).all(|(pk_, pop)| pop_ms::pop_verify(pk_, pop).is_ok());
+    let time_pop_vrf = time.elapsed().as_nanos();
 
-    if !benchmark{
-        if res == 0 {
-            println!("{} BLS Setup OK, completed in {}ns", l, fmt_time(&time_setup));
-        }else{
-            println!("{} BLS Setup FAILED, completed in {}ns", l, fmt_time(&time_setup));
-        } 
+    if !benchmark {
+        if pop_ok {
+            println!("All {} PoPs verified in {}ns\n", l, fmt_time(&time_pop_vrf));
+        } else {
+            println!("PoPs NOT verified.\n");
+        }
     }
 
     //sign
     let m: &str = "test message";
-    let mut sigma = ECP::new();
-    
-    let mut exp = vec![BIG::new(); l];
-    let mut h1 = HASH256::new();
 
-    let order = BIG::new_ints(&rom::CURVE_ORDER);
-    
     time = Instant::now();
-    for i in 0..l {
-        bls::core_sign(&mut sig[i], &m.as_bytes(), &sk[i]);
-    }
+    let sig: Vec<PartialSignature> = sk.iter().map(|sk_| pop_ms::sign(sk_, m.as_bytes())).collect::<Result<_>>()?;
     let time_sign = time.elapsed().as_nanos();
 
-    if !benchmark{
+    if !benchmark {
         println!("All {} signers signed in: {}ns\n", l, fmt_time(&time_sign));
     }
 
-    time = Instant::now();    
-    for i in 0..sig.len(){
-        sigma.add(&ECP::frombytes(&sig[i]));        
-    }
+    time = Instant::now();
+    let sigma = pop_ms::combine(&sig)?;
     let time_comb = time.elapsed().as_nanos();
-    
-    if !benchmark{
+
+    if !benchmark {
         print!("\nSignature : 0x");
-        printbinary(&ecptobytes(&sigma));
+        printbinary(&sigma.to_bytes());
         println!("Combining time taken: {}ns\n", fmt_time(&time_comb));
     }
 
     //pk aggregation
     time = Instant::now();
-    let apk = aggpk_nopop(&mut h1, &pk, &order);    
+    let apk = pop_ms::aggregate_public_keys(&pk)?;
     let time_apk = time.elapsed().as_nanos();
 
-    if !benchmark{
+    if !benchmark {
         print!("APK : 0x");
-        printbinary(&ecp2tobytes(&apk));
+        printbinary(&apk.to_bytes());
         println!("AggPK time taken: {}ns\n", fmt_time(&time_apk));
     }
 
-    //verify    
+    //verify, aggregating the PoP-checked keys on the fly
     time = Instant::now();
-    let mut r = pair::initmp();
-    sigma.neg();
-    pair::another(&mut r, &ECP2::generator(), &sigma);
-    pair::another(&mut r, &apk, &bls_hash_to_point(&m.as_bytes()));
-
-    let mut v = pair::miller(&mut r);
-    v = pair::fexp(&v);
-    if v.isunity() {
-        res = 0;
-    } else{
-        res = -1;
-    }
-    //res = bls::core_verify(&ecptobytes(&sigma), &m.as_bytes(), &ecp2tobytes(&apk));
+    let ok = pop_ms::fast_aggregate_verify(&pk, m.as_bytes(), &sigma).is_ok();
     let time_vrf = time.elapsed().as_nanos();
 
-    if !benchmark{
-        if res == 0 {
-            println!("Signature verified.");
-            println!("Verify time taken: {}ns\n", fmt_time(&time_vrf));
-        } else {
-            println!("Signature NOT verified.");
-            println!("Verify time taken: {}ns\n", fmt_time(&time_vrf));
-        }
+    if !benchmark {
+        print_verify(ok, time_vrf);
     }
 
-    return (time_setup, time_sign, time_comb, time_apk, time_vrf);
-}
-
-fn aggpk_nopop(h1: &mut HASH256, pkvec: &Vec<[u8; G2S]>, order : &BIG) -> ECP2 {
-    //concatenate all pk bytes
-
-    let mut apk = ECP2::new();
-
-    
-    for i in 0..pkvec.len(){    
-        apk.add(&ECP2::frombytes(&pkvec[i]));        
-    }
-    
-    apk
+    Ok(Timings {
+        setup: time_setup,
+        sign: time_sign,
+        combine: time_comb,
+        pkagg: time_apk,
+        verify: time_vrf,
+        extra: vec![("PoP Prove (one-off)", time_prove), ("PoP Verify (one-off)", time_pop_vrf)],
+    })
 }
 
-
-
-
-fn bdn_blsms(rng: &mut [RAND], benchmark: bool) -> (u128, u128, u128, u128, u128){    
+fn bdn_blsms(rng: &mut [RAND], benchmark: bool) -> Result<Timings> {
     let l = rng.len();
-    let mut sk: Vec<[u8; BGS]> = vec![[0; BGS]; l];
-    let mut pk: Vec<[u8; G2S]> = vec![[0; G2S]; l];
-    let mut sig: Vec<[u8; G1S]> = vec![[0; G1S]; l];
-
-    let mut res: isize = 0;
     let mut time;
-    let mut time_setup: u128 = 0;
 
     //setup
-    for i in 0..l{
-        //sk
-        sk[i] = [0; BGS];
-
-        //pk
-        pk[i] = [0; G2S];
-
-        time = Instant::now();
-        res += bls_setup(&mut rng[i], &mut sk[i], &mut pk[i]);
-        time_setup += time.elapsed().as_nanos();
-    }
-
-    /* check complexity difference
-    time = Instant::now();
-    pair::g1mul(&ECP::generator(), &BIG::frombytes(&sk[0]));
-    let mut ttime = time.elapsed().as_nanos();
-    println!("G1 mult. takes: {}ns", fmt_time(&ttime));
-
-    time = Instant::now();
-    pair::g2mul(&ECP2::generator(), &BIG::frombytes(&sk[0]));
-    ttime = time.elapsed().as_nanos();
-    println!("G2 mult. takes: {}ns", fmt_time(&ttime));
-    */
+    let (sk, pk, time_setup) = keygen_all(rng, bdn_ms::keygen)?;
 
-    if !benchmark{
-        if res == 0 {
-            println!("{} BLS Setup OK, completed in {}ns", l, fmt_time(&time_setup));
-        }else{
-            println!("{} BLS Setup FAILED, completed in {}ns", l, fmt_time(&time_setup));
-        } 
+    if !benchmark {
+        println!("{} BLS Setup OK, completed in {}ns", l, fmt_time(&time_setup));
     }
 
     //sign
     let m: &str = "test message";
-    let mut sigma = ECP::new();
-    
-    let mut exp = vec![BIG::new(); l];
-    let mut h1 = HASH256::new();
-
-    //concatenate all pk bytes
-    let mut allpk: Vec<u8> = vec![0; l * G2S];
 
-    for i in 0..l{
-        allpk.extend(pk[i].clone());
-    }
-    let order = BIG::new_ints(&rom::CURVE_ORDER);
-    
     time = Instant::now();
-    for i in 0..l {
-        exp[i] = BIG::modmul(&BIG::frombytes(&sk[i]), &hash_pks2big(&mut h1, &pk[i], &allpk, &order), &order);
-        let mut newsk: [u8; BGS] = [0; BGS];
-        exp[i].tobytes(&mut newsk);
-
-        bls::core_sign(&mut sig[i], &m.as_bytes(), &newsk);
-    }
+    let digest = bdn_ms::signer_set_digest(&pk)?;
+    let sig: Vec<PartialSignature> = map_signers(l, |i| bdn_ms::sign_with_digest(&sk[i], &pk[i], &digest, m.as_bytes()))?;
     let time_sign = time.elapsed().as_nanos();
 
-    if !benchmark{
+    if !benchmark {
         println!("All {} signers signed in: {}ns\n", l, fmt_time(&time_sign));
     }
 
-    time = Instant::now();    
-    for i in 0..sig.len(){
-        sigma.add(&ECP::frombytes(&sig[i]));        
-    }
+    time = Instant::now();
+    let sigma = bdn_ms::combine(&sig)?;
     let time_comb = time.elapsed().as_nanos();
-    
-    if !benchmark{
+
+    //combining again, checking every partial signature first
+    time = Instant::now();
+    bdn_ms::combine_verified(&sig, &pk, m.as_bytes())?;
+    let time_comb_vrf = time.elapsed().as_nanos();
+
+    if !benchmark {
         print!("\nSignature : 0x");
-        printbinary(&ecptobytes(&sigma));
-        println!("Combining time taken: {}ns\n", fmt_time(&time_comb));
+        printbinary(&sigma.to_bytes());
+        println!("Combining time taken: {}ns", fmt_time(&time_comb));
+        println!("Combining time taken with partial signature checks: {}ns\n", fmt_time(&time_comb_vrf));
     }
 
     //pk aggregation
     time = Instant::now();
-    let apk = bdn_aggpk(&mut h1, &pk, &order);    
+    let apk = bdn_ms::aggregate_public_keys(&pk)?;
     let time_apk = time.elapsed().as_nanos();
 
-    if !benchmark{
+    if !benchmark {
         print!("APK : 0x");
-        printbinary(&ecp2tobytes(&apk));
+        printbinary(&apk.to_bytes());
         println!("AggPK time taken: {}ns\n", fmt_time(&time_apk));
     }
 
-    //verify    
+    //verify
     time = Instant::now();
-    let mut r = pair::initmp();
-    sigma.neg();
-    pair::another(&mut r, &ECP2::generator(), &sigma);
-    pair::another(&mut r, &apk, &bls_hash_to_point(&m.as_bytes()));
-
-    let mut v = pair::miller(&mut r);
-    v = pair::fexp(&v);
-    if v.isunity() {
-        res = 0;
-    } else{
-        res = -1;
-    }
-    //res = bls::core_verify(&ecptobytes(&sigma), &m.as_bytes(), &ecp2tobytes(&apk));
+    let ok = bdn_ms::verify(&sigma, m.as_bytes(), &apk).is_ok();
     let time_vrf = time.elapsed().as_nanos();
 
-    if !benchmark{
-        if res == 0 {
-            println!("Signature verified.");
-            println!("Verify time taken: {}ns\n", fmt_time(&time_vrf));
-        } else {
-            println!("Signature NOT verified.");
-            println!("Verify time taken: {}ns\n", fmt_time(&time_vrf));
-        }
-    }
-
-    return (time_setup, time_sign, time_comb, time_apk, time_vrf);
-}
-
-fn bdn_aggpk(h1: &mut HASH256, pkvec: &Vec<[u8; G2S]>, order : &BIG) -> ECP2 {
-    //concatenate all pk bytes
-    let mut allpk: Vec<u8> = vec![0; pkvec.len() * G2S];
-
-    for i in 0..pkvec.len(){
-        allpk.extend(pkvec[i].clone());
+    if !benchmark {
+        print_verify(ok, time_vrf);
     }
 
-    let mut apk = ECP2::new();
-
-    
-    for i in 0..pkvec.len(){    
-        apk.add(
-            &pair::g2mul(&ECP2::frombytes(&pkvec[i]), &hash_pks2big(h1, &pkvec[i], &allpk, &order))
-        );
-        
-        /*
-        let time0 = Instant::now();
-        pair::g2mul(&ECP2::generator(), &BIG::new_int(2));
-        let tt = time0.elapsed().as_nanos();
-        println!("Time taken by g2mul in bdn: {}ns", fmt_time(&tt));
-        */
-    }
-    
-    apk
+    Ok(Timings {
+        setup: time_setup,
+        sign: time_sign,
+        combine: time_comb,
+        pkagg: time_apk,
+        verify: time_vrf,
+        extra: vec![("Combine (verified)", time_comb_vrf)],
+    })
 }
 
-
-
-fn our_blsms(rng: &mut [RAND], ell: usize, benchmark: bool) -> (u128, u128, u128, u128, u128){    
-    let mut h = HASH256::new();
+fn our_blsms(rng: &mut [RAND], ell: ChallengeLength, benchmark: bool) -> Result<Timings> {
     let l = rng.len();
-    let mut sk: Vec<[u8; BGS]> = vec![[0; BGS]; l];
-    let mut pk: Vec<[u8; G2S]> = vec![[0; G2S]; l];
-    let mut sig: Vec<[u8; G1S]> = vec![[0; G1S]; l];
-    let mut s1: ECP;
-    
-    let mut res: isize = 0;
     let mut time;
-    let mut time_setup: u128 = 0;
 
     //setup
-    for i in 0..l{
-        //sk
-        sk[i] = [0; BGS];
-
-        //pk
-        pk[i] = [0; G2S];
-
-        time = Instant::now();
-        res += bls_setup(&mut rng[i], &mut sk[i], &mut pk[i]);
-        time_setup += time.elapsed().as_nanos();
-    }
+    let (sk, pk, time_setup) = keygen_all(rng, our_ms::keygen)?;
 
-    if !benchmark{
-        if res == 0 {
-            println!("{} BLS Setup OK, completed in {}ns", l, fmt_time(&time_setup));
-        }else{
-            println!("{} BLS Setup FAILED, completed in {}ns", l, fmt_time(&time_setup));
-        } 
+    if !benchmark {
+        println!("{} BLS Setup OK, completed in {}ns", l, fmt_time(&time_setup));
     }
 
     //sign
     let m: &str = "test message";
-    
-    let order = BIG::new_ints(&rom::CURVE_ORDER);
-    
+
     time = Instant::now();
-    for i in 0..l {
-        bls::core_sign(&mut sig[i], &m.as_bytes(), &sk[i]);
-    }
+    let sig: Vec<PartialSignature> = map_signers(l, |i| our_ms::sign(&sk[i], m.as_bytes()))?;
     let time_sign = time.elapsed().as_nanos();
 
-    if !benchmark{
+    if !benchmark {
         println!("All {} signers signed in: {}ns\n", l, fmt_time(&time_sign));
     }
 
-    time = Instant::now();    
-    s1 = our_combiner(&sig, &BIG::frombytes(&sk[0]), &order, &hash_2big(&mut h, &sig[0], ell, &order));
-    
+    //the challenge is bound to the message, the committee's aggregated key and this run
+    let ctx = ChallengeContext::for_key(&our_ms::aggregate_public_keys(&pk)?).bind_session_id(b"our_blsms");
+
+    time = Instant::now();
+    let sigma = our_ms::combine(&sig, &sk[0], m.as_bytes(), ell, &ctx)?;
     let time_comb = time.elapsed().as_nanos();
-    
-    if !benchmark{
+
+    //combining again, checking every partial signature first
+    time = Instant::now();
+    our_ms::combine_verified(&sig, &pk, &sk[0], m.as_bytes(), ell, &ctx)?;
+    let time_comb_vrf = time.elapsed().as_nanos();
+
+    if !benchmark {
+        let bytes = sigma.to_bytes();
         print!("\nS1 : 0x");
-        printbinary(&ecptobytes(&s1));
+        printbinary(&bytes[1..1 + G1S]);
         print!("\nS2 : 0x");
-        printbinary(&sig[0]);
-        println!("Combining time taken: {}ns\n", fmt_time(&time_comb));
+        printbinary(&bytes[1 + G1S..]);
+        println!("Combining time taken: {}ns", fmt_time(&time_comb));
+        println!("Combining time taken with partial signature checks: {}ns\n", fmt_time(&time_comb_vrf));
+
+        //a partial signature swapped between the first two signers is caught and named
+        if l > 1 {
+            let mut swapped = sig.clone();
+            swapped.swap(0, 1);
+            if let Err(e) = our_ms::combine_verified(&swapped, &pk, &sk[0], m.as_bytes(), ell, &ctx) {
+                println!("Combining swapped partial signatures: {}\n", e);
+            }
+        }
     }
 
-    //pk aggregation    
+    //pk aggregation
     time = Instant::now();
-    let (k1, k2) = our_aggpk(&pk);        
+    let apk = our_ms::aggregate_public_keys(&pk)?;
     let time_apk = time.elapsed().as_nanos();
 
-    if !benchmark{
+    //previous aggregation with one scalar multiplication per signer, for comparison
+    time = Instant::now();
+    our_ms::aggregate_public_keys_mul(&pk)?;
+    let time_apk_mul = time.elapsed().as_nanos();
+
+    //the last signer joining a committee whose (K1, K2) is already known
+    let mut state = AggregateKeyState::from_keys(&pk[..l - 1]);
+    time = Instant::now();
+    state.add_signer(&pk[l - 1]);
+    let state_apk = state.aggregate_key()?;
+    let time_apk_add = time.elapsed().as_nanos();
+
+    if !benchmark {
+        let bytes = apk.to_bytes();
         print!("K1 : 0x");
-        printbinary(&ecp2tobytes(&k1));
+        printbinary(&bytes[..G2S]);
         println!();
         print!("K2 : 0x");
-        printbinary(&ecp2tobytes(&k2));
-        println!("AggPK time taken: {}ns\n", fmt_time(&time_apk));
+        printbinary(&bytes[G2S..]);
+        println!("AggPK time taken: {}ns", fmt_time(&time_apk));
+        println!("AggPK time taken with scalar multiplications: {}ns", fmt_time(&time_apk_mul));
+        println!("AggPK time taken for one joining signer: {}ns", fmt_time(&time_apk_add));
+        if state_apk.to_bytes() == bytes {
+            println!("Updated aggregated key matches.\n");
+        } else {
+            println!("Updated aggregated key does NOT match.\n");
+        }
     }
-    
-    //verify    
-    time = Instant::now();    
-    //e(S1 + S2, g2)    
-    s1.add(&ECP::frombytes(&sig[0]));
-    s1.neg();
-    let mut r = pair::initmp();
-    pair::another(&mut r, &ECP2::generator(), &s1);
-
-    //e(S2 + H(m)^{H(S_2)}, K1)
-    //pair::another(&mut r, &k1, &ECP::frombytes(&sig[0]));
-    let hash_m = &bls_hash_to_point(&m.as_bytes());
-    let mut temp = ECP::frombytes(&sig[0]);
-    temp.add(&hash_m.mul(&hash_2big(&mut HASH256::new(), &sig[0], ell, &order)));
-    pair::another(&mut r, &k1, &temp);
-
-    //e(H(m), K2)
-    pair::another(&mut r, &k2, &hash_m);
-
-    let mut v = pair::miller(&mut r);
-    v = pair::fexp(&v);
-    if v.isunity() {
-        res = 0;
-    } else{
-        res = -1;
-    }
-    
+
+    //verify
+    time = Instant::now();
+    let ok = our_ms::verify(&sigma, m.as_bytes(), &apk, ell, &ctx).is_ok();
     let time_vrf = time.elapsed().as_nanos();
 
-    if !benchmark{
-        if res == 0 {
-            println!("Signature verified.");
-            println!("Verify time taken: {}ns\n", fmt_time(&time_vrf));
+    //line functions of g2, K1 and K2 computed once, then reused by every verification
+    time = Instant::now();
+    let prepared = PreparedVerifyingKey::new(&apk);
+    let time_prepare = time.elapsed().as_nanos();
+
+    time = Instant::now();
+    let ok_prepared = prepared.verify(&sigma, m.as_bytes(), ell, &ctx).is_ok();
+    let time_vrf_prepared = time.elapsed().as_nanos();
+
+    if !benchmark {
+        print_verify(ok, time_vrf);
+
+        println!("Key preparation time taken: {}ns", fmt_time(&time_prepare));
+        if ok_prepared {
+            println!("Signature verified with prepared key.");
         } else {
-            println!("Signature NOT verified.");
-            println!("Verify time taken: {}ns\n", fmt_time(&time_vrf));
+            println!("Signature NOT verified with prepared key.");
+        }
+        println!("Prepared verify time taken: {}ns\n", fmt_time(&time_vrf_prepared));
+
+        //incremental signing, each signer folding into the running multisignature
+        let mut session = our_ms::IncrementalSession::start(&sk[0], m.as_bytes(), ell, &ctx);
+        for (i, sk_) in sk.iter().enumerate().skip(1) {
+            session.append(sk_, i + 1)?;
+        }
+        let sigma = session.finalize();
+
+        if our_ms::verify(&sigma, m.as_bytes(), &apk, ell, &ctx).is_ok() {
+            println!("Incrementally signed multisignature verified.\n");
+        } else {
+            println!("Incrementally signed multisignature NOT verified.\n");
+        }
+
+        //canonical order: partial signatures arrive in any order and are placed by key
+        let set = SignerSet::canonical(&pk)?;
+        let first = pk.iter().position(|pk_| set.index_of(pk_) == Some(1)).unwrap();
+        let arrived: Vec<(PublicKey, PartialSignature)> = pk.iter().cloned().zip(sig.iter().cloned()).rev().collect();
+        let set_apk = our_ms::aggregate_public_keys(set.keys())?;
+        let set_ctx = ChallengeContext::for_key(&set_apk);
+        let sigma = our_ms::combine_in_set(&set, &arrived, &sk[first], m.as_bytes(), ell, &set_ctx)?;
+
+        if our_ms::verify(&sigma, m.as_bytes(), &set_apk, ell, &set_ctx).is_ok() {
+            println!("Canonically ordered multisignature verified.\n");
+        } else {
+            println!("Canonically ordered multisignature NOT verified.\n");
         }
-    }
-    
-    return (time_setup, time_sign, time_comb, time_apk, time_vrf);
-}
 
-fn our_combiner(sig: &Vec<[u8; G1S]>, sk: &BIG, order: &BIG, start: &BIG) -> ECP{
-    let mut sigma = ECP::new();
+        //multi-message aggregation: signer i signs its own message
+        let msgs: Vec<String> = (1..=l).map(|i| format!("{} from signer {}", m, i)).collect();
+        let mut session = our_aggregate::AggregateSession::start(&sk[0], msgs[0].as_bytes(), ell, &ctx);
+        for (i, sk_) in sk.iter().enumerate().skip(1) {
+            session.append(sk_, i + 1, msgs[i].as_bytes())?;
+        }
+        let sigma = session.finalize();
+        let signers: Vec<SignedMessage> = pk.iter().zip(&msgs).enumerate().map(|(i, (pk_, m_))| (i + 1, pk_, m_.as_bytes())).collect();
 
-    if start.iszilch(){
-        for i in 0..sig.len(){        
-            let e = BIG::modadd(&sk, 
-                                    &BIG::new_int((i as isize) +1), 
-                                    &order);
-            
-            sigma.add(&pair::g1mul(&ECP::frombytes(&sig[i]), &e));        
+        if our_aggregate::verify(&sigma, &signers, ell, &ctx).is_ok() {
+            println!("Aggregate signature on {} messages verified.\n", l);
+        } else {
+            println!("Aggregate signature on {} messages NOT verified.\n", l);
         }
-    }else{
-        for i in 0..sig.len(){        
-            let e = BIG::modadd(&sk, 
-                                    &BIG::modadd(&start, &BIG::new_int((i as isize) +1), &order), 
-                                    &order);
-            
-            sigma.add(&pair::g1mul(&ECP::frombytes(&sig[i]), &e));        
+
+        //subset signing: only the odd registered indices take part
+        let indices: Vec<usize> = (1..=l).step_by(2).collect();
+        let participation = Participation::from_indices(l, &indices)?;
+        let subset_sig: Vec<PartialSignature> = indices.iter().map(|&i| sig[i - 1].clone()).collect();
+        let subset_ctx = ChallengeContext::for_key(&our_subset::aggregate_public_keys(&pk, &participation)?);
+        let sigma = our_subset::combine(&participation, &subset_sig, &sk[0], m.as_bytes(), ell, &subset_ctx)?;
+
+        if our_subset::verify(&sigma, m.as_bytes(), &pk, ell, &subset_ctx).is_ok() {
+            println!("Multisignature of {} out of {} signers verified.\n", indices.len(), l);
+        } else {
+            println!("Multisignature of {} out of {} signers NOT verified.\n", indices.len(), l);
         }
     }
-    
 
-    sigma
+    Ok(Timings {
+        setup: time_setup,
+        sign: time_sign,
+        combine: time_comb,
+        pkagg: time_apk,
+        verify: time_vrf,
+        extra: vec![
+            ("Combine (verified)", time_comb_vrf),
+            ("PK Agg (scalar mul)", time_apk_mul),
+            ("PK Agg (one joining)", time_apk_add),
+            ("Prepare VK (one-off)", time_prepare),
+            ("Verify (prepared)", time_vrf_prepared),
+        ],
+    })
 }
 
-fn our_aggpk(pkvec: &Vec<[u8; G2S]>) -> (ECP2, ECP2) {//, order: &BIG, start: &BIG) -> (ECP2, ECP2) {
-    //concatenate all pk bytes
-    let mut k1 = ECP2::new();
-    let mut k2 = ECP2::new();
-
-    
-    //if start.iszilch(){
-        for i in 0..pkvec.len(){                    
-            k2.add(
-                //&pair::g2mul(&ECP2::frombytes(&pkvec[i]), &BIG::new_int((i as isize) +1))            
-                &ECP2::frombytes(&pkvec[i]).mul(&BIG::new_int((i as isize) +1))
-            );
-            k1.add(
-                &ECP2::frombytes(&pkvec[i])
-            );
-
-            /*
-            let mut time0 = Instant::now();
-            pair::g2mul(&ECP2::generator(), &BIG::new_int(2));
-            let mut tt = time0.elapsed().as_nanos();
-            println!("Time taken by g2mul in ours: {}ns", fmt_time(&tt));
-
-            
-            time0 = Instant::now();
-            pair::g2mul(&ECP2::generator(), &BIG::modadd(&start, &BIG::new_int(2), &order));
-            tt = time0.elapsed().as_nanos();
-            println!("Time taken by g2mul in ours: {}ns", fmt_time(&tt));*/
-        }    
-    //}else{
-    //    for i in 0..pkvec.len(){        
-    //        k1.add(
-    //            &ECP2::frombytes(&pkvec[i])
-    //        );
-    //        k2.add(
-    //            //&pair::g2mul(&ECP2::frombytes(&pkvec[i]), &BIG::modadd(&start, &BIG::new_int((i as isize) +1), &order))
-    //            &ECP2::frombytes(&pkvec[i]).mul(&BIG::modadd(&start, &BIG::new_int((i as isize) +1), &order))            
-    //        );
-    //    }
-    //}
-    
-
-    k2.add(&ECP2::frombytes(&pkvec[0]));
-
-    (k1,k2)
-}
+fn run_benchmark(round: u128, mut run: impl FnMut() -> Result<Timings>) -> Result<()> {
+    let mut total = Timings::default();
+
+    for i in 0..round {
+        print!("\rRunning round {}/{}", i, round);
+        io::stdout().flush().unwrap();
+
+        let t = run()?;
+        total.setup += t.setup;
+        total.sign += t.sign;
+        total.combine += t.combine;
+        total.pkagg += t.pkagg;
+        total.verify += t.verify;
+
+        if total.extra.is_empty() {
+            total.extra = t.extra;
+        } else {
+            for (sum, (_, x)) in total.extra.iter_mut().zip(t.extra) {
+                sum.1 += x;
+            }
+        }
+    }
 
-fn hash_2big(h: &mut HASH256, sig2: &[u8], ell: usize, order: &BIG) -> BIG{
-    if ell == 0{
-        return BIG::new();
+    print!("\r{}\r", " ".repeat(30));
+    io::stdout().flush().unwrap();
+
+    println!("Average timing for {} rounds:\n", round);
+    println!("Setup time taken  : {}ns", fmt_time(&(total.setup / round)));
+    println!("Signing time taken: {}ns", fmt_time(&(total.sign / round)));
+    println!("Combine time taken: {}ns", fmt_time(&(total.combine / round)));
+    println!("PK Agg time taken : {}ns", fmt_time(&(total.pkagg / round)));
+    println!("Verify time taken : {}ns", fmt_time(&(total.verify / round)));
+
+    for (label, sum) in &total.extra {
+        println!("{} time taken: {}ns", label, fmt_time(&(sum / round)));
     }
 
-    for i in 0..sig2.len(){
-        h.process(sig2[i]);
+    Ok(())
+}
+
+//BDN coefficient derivation and key aggregation for growing signer sets; with the signer
+//set hashed once both grow linearly, which the per-signer columns make visible
+fn bdn_scaling(max: usize) -> Result<()> {
+    let mut rng = gen_seed();
+    let mut pk: Vec<PublicKey> = Vec::with_capacity(max);
+
+    print!("Generating {} keys...", max);
+    io::stdout().flush().unwrap();
+    for _ in 0..max {
+        pk.push(bdn_ms::keygen(&mut rng)?.1);
     }
+    println!("\n");
 
-    let mut output = h.hash().to_vec();
+    println!("{:>8} | {:>19} | {:>15} | {:>19} | {:>15}", "signers", "coefficients (ns)", "per signer (ns)", "aggregation (ns)", "per signer (ns)");
 
-    output = output[..(ell/8)].to_vec();    
-    
-    if output.len() < BGS {
-        let extra = BGS - output.len();
-        let mut zeros: Vec<u8> = vec![0; extra];
-        zeros.append(&mut output);        
+    for n in [max / 10, max / 5, max / 2, max] {
+        if n == 0 {
+            continue;
+        }
 
-        let mut temp = BIG::frombytes(&zeros);
-        temp.rmod(&order);
-        return temp;
+        let time = Instant::now();
+        let digest = bdn_ms::signer_set_digest(&pk[..n])?;
+        for pk_ in &pk[..n] {
+            bdn_ms::coefficient(pk_, &digest);
+        }
+        let time_coef = time.elapsed().as_nanos();
+
+        let time = Instant::now();
+        bdn_ms::aggregate_public_keys(&pk[..n])?;
+        let time_apk = time.elapsed().as_nanos();
+
+        println!(
+            "{:>8} | {:>19} | {:>15} | {:>19} | {:>15}",
+            n,
+            fmt_time(&time_coef),
+            fmt_time(&(time_coef / n as u128)),
+            fmt_time(&time_apk),
+            fmt_time(&(time_apk / n as u128))
+        );
     }
 
-    let mut temp = BIG::frombytes(&output);
-    temp.rmod(&order);
-    return temp;
+    Ok(())
 }
 
-fn hash_pks2big(h1: &mut HASH256, pknow: &[u8], input: &[u8], order: &BIG) -> BIG{
-    for i in 0..pknow.len(){
-        h1.process(pknow[i]);
+//the rogue-key attack of `attack` against every scheme; returns whether only BLS-MS-noPoP
+//accepted the forgery
+fn rogue_key_demo(honest: usize) -> Result<bool> {
+    let mut rng = gen_seed();
+    let m = b"transfer everything to the attacker";
+
+    let mut pk: Vec<PublicKey> = Vec::with_capacity(honest + 1);
+    for _ in 0..honest {
+        pk.push(nopop_ms::keygen(&mut rng)?.1);
     }
 
-    for i in 0..input.len(){
-        h1.process(input[i]);
+    //the attacker announces its key last, after seeing all honest keys
+    let (x, _) = nopop_ms::keygen(&mut rng)?;
+    pk.push(attack::rogue_key(&x, &pk));
+
+    let ell = ChallengeLength::new(128)?;
+    let ctx = ChallengeContext::for_key(&our_ms::aggregate_public_keys(&pk)?);
+
+    let nopop = nopop_ms::verify(&attack::forge_nopop(&x, m), m, &nopop_ms::aggregate_public_keys(&pk)?).is_ok();
+    let bdn = bdn_ms::verify(&attack::forge_bdn(&x, m), m, &bdn_ms::aggregate_public_keys(&pk)?).is_ok();
+    let our = our_ms::verify(&attack::forge_our(&x, m, ell, &ctx), m, &our_ms::aggregate_public_keys(&pk)?, ell, &ctx).is_ok();
+
+    println!("Rogue key against {} honest signers:\n", honest);
+    for (name, accepted) in [("BLS-MS-noPoP", nopop), ("BDN-MS", bdn), ("OUR-MS", our)] {
+        println!("{:>13} : forgery {}", name, if accepted { "ACCEPTED" } else { "rejected" });
     }
 
-    let mut output = h1.hash().to_vec();
+    Ok(nopop && !bdn && !our)
+}
+
+//the generic forgery of `forgery_lab` at challenge length `ell`, without and with the
+//aggregated key bound into the challenge
+fn forgery_lab(ell: ChallengeLength, attempts: u64) -> Result<()> {
+    let mut rng = gen_seed();
+    let m = b"forgery lab";
+
+    let mut pk: Vec<PublicKey> = Vec::with_capacity(4);
+    for _ in 0..4 {
+        pk.push(our_ms::keygen(&mut rng)?.1);
+    }
 
-    if output.len() < BGS {
-        let extra = BGS - output.len();
-        let mut zeros: Vec<u8> = vec![0; extra];
-        zeros.append(&mut output);        
+    println!("Forging OUR-MS with ell = {} against {} honest signers:\n", ell.bits(), pk.len());
+    println!("{:>10} | {:>10} | {:>9} | {:>12} | {:>12}", "key", "attempts", "successes", "observed", "bound");
 
-        let mut temp = BIG::frombytes(&zeros);
-        temp.rmod(&order);
+    //unbound, every attempt succeeds and costs pairings, so a few are enough
+    for (bind, n) in [(false, attempts.min(16)), (true, attempts)] {
+        let report = forgery_lab::run(&pk, m, ell, n, bind, &mut rng)?;
 
-        return BIG::frombytes(&zeros);
+        println!(
+            "{:>10} | {:>10} | {:>9} | {:>12.3e} | {:>12.3e}",
+            if bind { "bound" } else { "unbound" },
+            report.attempts,
+            report.successes,
+            report.success_rate(),
+            report.bound()
+        );
     }
 
-    let mut temp = BIG::frombytes(&output);
-    temp.rmod(&order);
+    Ok(())
+}
 
-    return BIG::frombytes(&output);
+//`count` OUR-MS multisignatures of a 4-signer committee on distinct messages, verified one
+//by one and as a batch, then again with one of them corrupted
+fn batch_verify(count: usize) -> Result<()> {
+    let mut rng = gen_seed();
+    let ell = ChallengeLength::new(128)?;
+
+    let mut sk: Vec<SecretKey> = Vec::with_capacity(4);
+    let mut pk: Vec<PublicKey> = Vec::with_capacity(4);
+    for _ in 0..4 {
+        let (sk_, pk_) = our_ms::keygen(&mut rng)?;
+        sk.push(sk_);
+        pk.push(pk_);
+    }
+
+    let apk = our_ms::aggregate_public_keys(&pk)?;
+    let ctx = ChallengeContext::for_key(&apk);
+    let msgs: Vec<Vec<u8>> = (0..count).map(|i| format!("message {}", i).into_bytes()).collect();
+
+    print!("Signing {} multisignatures...", count);
+    io::stdout().flush().unwrap();
+    let mut sigs = Vec::with_capacity(count);
+    for m in &msgs {
+        let sig: Vec<PartialSignature> = sk.iter().map(|sk_| our_ms::sign(sk_, m)).collect::<Result<_>>()?;
+        sigs.push(our_ms::combine(&sig, &sk[0], m, ell, &ctx)?);
+    }
+    println!("\n");
+
+    let time = Instant::now();
+    let ok = sigs.iter().zip(&msgs).all(|(sig, m)| our_ms::verify(sig, m, &apk, ell, &ctx).is_ok());
+    let time_single = time.elapsed().as_nanos();
+    println!("One by one: {} in {}ns", if ok { "all verified" } else { "NOT all verified" }, fmt_time(&time_single));
+
+    let time = Instant::now();
+    let prepared = PreparedVerifyingKey::new(&apk);
+    let ok = sigs.iter().zip(&msgs).all(|(sig, m)| prepared.verify(sig, m, ell, &ctx).is_ok());
+    let time_prepared = time.elapsed().as_nanos();
+    println!(
+        "One by one, prepared key: {} in {}ns",
+        if ok { "all verified" } else { "NOT all verified" },
+        fmt_time(&time_prepared)
+    );
+
+    let items: Vec<BatchItem> = sigs.iter().zip(&msgs).map(|(sig, m)| BatchItem { sig, m, apk: &apk, ell, ctx: &ctx }).collect();
+
+    let time = Instant::now();
+    let invalid = our_batch::verify_batch(&items, &mut rng);
+    let time_batch = time.elapsed().as_nanos();
+    println!("As a batch: {} invalid in {}ns", invalid.len(), fmt_time(&time_batch));
+
+    //a signature checked against the wrong message
+    let mut items = items;
+    let bad = count / 2;
+    items[bad].m = &msgs[(bad + 1) % count];
+
+    let time = Instant::now();
+    let invalid = our_batch::verify_batch(&items, &mut rng);
+    let time_bisect = time.elapsed().as_nanos();
+    println!("As a batch with #{} corrupted: invalid {:?} in {}ns", bad, invalid, fmt_time(&time_bisect));
+
+    Ok(())
 }
 
-fn fmt_time(time: &u128) -> String{
-    if (time % 1000000000000) / 1000000000 == 0 && (time % 1000000000) / 1000000 == 0 {
-        return format!("{:3},{:03}", (time % 1000000) / 1000 , time % 1000)
-    } else if (time % 1000000000000) / 1000000000 == 0 {
-        return format!("{:3},{:03},{:03}", (time % 1000000000) / 1000000 , (time % 1000000) / 1000 , time % 1000)
-    } else {
-        return format!("{:3},{:03},{:03},{:03}", (time % 1000000000000) / 1000000000, (time % 1000000000) / 1000000 , (time % 1000000) / 1000 , time % 1000)
+//keeps asking until the answer parses and passes `valid`
+fn read_number<T: FromStr>(prompt: &str, valid: impl Fn(&T) -> bool) -> T {
+    let mut input = String::new();
+
+    loop {
+        print!("{}", prompt);
+        io::stdout().flush().unwrap();
+
+        input.clear();
+        if io::stdin().read_line(&mut input).unwrap() == 0 {
+            eprintln!("\nNo input, exiting.");
+            process::exit(2);
+        }
+
+        match input.trim().parse() {
+            Ok(n) if valid(&n) => return n,
+            _ => println!("Invalid input '{}', please try again.", input.trim()),
+        }
     }
 }
 
-fn gen_seed() -> RAND{
-    let mut raw: [u8; 100] = [0; 100];
-    let mut rng = RAND::new();
-    rng.clean();
-    OsRng.fill_bytes(&mut raw);
-    rng.seed(100, &raw);
+fn read_ell() -> ChallengeLength {
+    let bits = read_number(
+        "What's the bit length of 'ell' for OUR-MS? Insert a number of bits (0 - 255): ",
+        |&ell| ChallengeLength::new(ell).is_ok(),
+    );
 
-    return rng;
+    ChallengeLength::new(bits).unwrap()
 }
 
-fn main() {
-    //use mcore::arch;
-    //println!("{} bit build", arch::CHUNK);
-    
-    //bls(&mut gen_seed());
-
+fn bench() -> Result<()> {
     //set to false if want to see the details
     let mut benchmark = false;
     let mut bdn_only = false;
     let mut our_only = false;
     let mut nopop_only = false;
+    let mut pop_only = false;
     let mut round: u128 = 1;
     let mut input = String::new();
-    let mut ell : usize = 0;
+    let mut ell = ChallengeLength::new(0).unwrap();
 
     print!("Run a benchmark? Type 'Y' for yes, or press ENTER to run single execution: ");
-    io::stdout().flush().unwrap(); 
+    io::stdout().flush().unwrap();
 
     io::stdin().read_line(&mut input).unwrap();
     input = input.trim().to_string();
@@ -684,262 +744,201 @@ fn main() {
             benchmark = true;
 
             input.clear();
-            print!("Run which scheme? Type '1' for BDN-MS only, '2' for OUR-MS only, '3' for BLS-MS-noPoP press ENTER for all: ");
-            io::stdout().flush().unwrap(); 
+            print!("Run which scheme? Type '1' for BDN-MS only, '2' for OUR-MS only, '3' for BLS-MS-noPoP, '4' for BLS-MS-PoP, press ENTER for all: ");
+            io::stdout().flush().unwrap();
 
             io::stdin().read_line(&mut input).unwrap();
             input = input.trim().to_string();
 
             match input.as_str() {
-                "1" => { bdn_only = true;}
-                "2" => { 
+                "1" => {
+                    bdn_only = true;
+                }
+                "2" => {
                     our_only = true;
-                    
-                    print!("What's the bit length of 'ell' for OUR-MS? Insert a number in multiple of 8 (0 - 64): ");
-                    io::stdout().flush().unwrap(); 
-
-                    input.clear();
-                    io::stdin().read_line(&mut input).unwrap();
-
-                    ell = input.trim().parse().unwrap();
+                    ell = read_ell();
+                }
+                "3" => {
+                    nopop_only = true;
+                }
+                "4" => {
+                    pop_only = true;
                 }
-                "3" => { nopop_only = true;}
                 _ => {
-                    bdn_only = true; our_only = true; nopop_only = true;
-
-                    print!("What's the bit length of 'ell' for OUR-MS? Insert a number in multiple of 8 (0 - 64): ");
-                    io::stdout().flush().unwrap(); 
-
-                    input.clear();
-                    io::stdin().read_line(&mut input).unwrap();
-
-                    ell = input.trim().parse().unwrap();
+                    bdn_only = true;
+                    our_only = true;
+                    nopop_only = true;
+                    pop_only = true;
+                    ell = read_ell();
                 }
             }
 
-            print!("Run benchmark for how many rounds? ");
-            io::stdout().flush().unwrap(); 
-
-            input.clear();
-            io::stdin().read_line(&mut input).unwrap();
-            round = input.trim().parse().unwrap();            
+            round = read_number("Run benchmark for how many rounds? ", |&n| n > 0);
         }
-        _ => {            
-            bdn_only = true; our_only = true; nopop_only = true;
+        _ => {
+            bdn_only = true;
+            our_only = true;
+            nopop_only = true;
+            pop_only = true;
             println!("Run single execution... ");
 
-            print!("What's the bit length of 'ell' for OUR-MS? Insert a number in multiple of 8 (0 - 64): ");
-            io::stdout().flush().unwrap(); 
-
-            input.clear();
-            io::stdin().read_line(&mut input).unwrap();
-
-            ell = input.trim().parse().unwrap();
+            ell = read_ell();
         }
     }
 
-    print!("How many MS signers? ");
-    io::stdout().flush().unwrap(); 
-
-    input.clear();
-    io::stdin().read_line(&mut input).unwrap();
-
-    let input_num: usize = input.trim().parse().unwrap();
+    let input_num: usize = read_number("How many MS signers? ", |&n| n > 0);
 
     let mut rng: Vec<RAND> = Vec::with_capacity(input_num);
     for _ in 0..input_num {
         rng.push(gen_seed());
     }
 
-    
-    if bdn_only{
-
+    if bdn_only {
         println!("\n=================================");
         println!("This is BDN-MS with {} signers\n", input_num);
         println!("=================================\n");
         io::stdout().flush().unwrap();
-        
-        if benchmark{
-            let mut setup : u128 = 0;
-            let mut sign : u128 = 0;
-            let mut combine : u128 = 0;
-            let mut pkagg : u128 = 0;
-            let mut verify : u128 = 0;
-
-            for i in 0..round{
-                print!("\rRunning round {}/{}", i, round);
-                io::stdout().flush().unwrap();
-
-                let (setup_, sign_, combine_, pkagg_, verify_) = bdn_blsms(&mut rng, benchmark);
-                setup += setup_;
-                sign += sign_;
-                combine += combine_;
-                pkagg += pkagg_;
-                verify += verify_;
-            }
-
-            print!("\r{}{}", " ".repeat(30), "\r");
-            io::stdout().flush().unwrap();
 
-
-            setup /= round;
-            sign /= round;
-            combine /= round;
-            pkagg /= round;
-            verify /= round;
-            
-            println!("Average timing for {} rounds:\n", round);
-            println!("Setup time taken  : {}ns", fmt_time(&setup));
-            println!("Signing time taken: {}ns", fmt_time(&sign));
-            println!("Combine time taken: {}ns", fmt_time(&combine));
-            println!("PK Agg time taken : {}ns", fmt_time(&pkagg));
-            println!("Verify time taken : {}ns", fmt_time(&verify));
-        }else{
-            bdn_blsms(&mut rng, benchmark);
+        if benchmark {
+            run_benchmark(round, || bdn_blsms(&mut rng, benchmark))?;
+        } else {
+            bdn_blsms(&mut rng, benchmark)?;
         }
     }
 
-    if our_only{
+    if our_only {
         println!("\n=================================");
         println!("\nThis is Our MS with {} signers", input_num);
         println!("=================================\n");
         io::stdout().flush().unwrap();
 
-        if benchmark{
-            let mut setup : u128 = 0;
-            let mut sign : u128 = 0;
-            let mut combine : u128 = 0;
-            let mut pkagg : u128 = 0;
-            let mut verify : u128 = 0;
-
-            for i in 0..round{
-                print!("\rRunning round {}/{}", i, round);
-                io::stdout().flush().unwrap();
-
-                let (setup_, sign_, combine_, pkagg_, verify_) = our_blsms(&mut rng, ell, benchmark);
-                setup += setup_;
-                sign += sign_;
-                combine += combine_;
-                pkagg += pkagg_;
-                verify += verify_;
-            }
+        if benchmark {
+            run_benchmark(round, || our_blsms(&mut rng, ell, benchmark))?;
+        } else {
+            our_blsms(&mut rng, ell, benchmark)?;
+        }
+    }
 
-            print!("\r{}{}", " ".repeat(30), "\r");
-            io::stdout().flush().unwrap();
+    if nopop_only {
+        println!("\n=================================");
+        println!("\nThis is BLS MS without PoP with {} signers", input_num);
+        println!("=================================\n");
+        io::stdout().flush().unwrap();
 
+        if benchmark {
+            run_benchmark(round, || blsms_nopop(&mut rng, benchmark))?;
+        } else {
+            blsms_nopop(&mut rng, benchmark)?;
+        }
+    }
 
-            setup /= round;
-            sign /= round;
-            combine /= round;
-            pkagg /= round;
-            verify /= round;
-            
-            println!("Average timing for {} rounds:\n", round);
-            println!("Setup time taken  : {}ns", fmt_time(&setup));
-            println!("Signing time taken: {}ns", fmt_time(&sign));
-            println!("Combine time taken: {}ns", fmt_time(&combine));
-            println!("PK Agg time taken : {}ns", fmt_time(&pkagg));
-            println!("Verify time taken : {}ns", fmt_time(&verify));
-        }else{
-            our_blsms(&mut rng, ell, benchmark);
-        }    
-    }
-
-    if nopop_only{
+    if pop_only {
         println!("\n=================================");
-        println!("\nThis is BLS MS without PoP with {} signers", input_num);
+        println!("\nThis is BLS MS with PoP with {} signers", input_num);
         println!("=================================\n");
         io::stdout().flush().unwrap();
 
-        if benchmark{
-            let mut setup : u128 = 0;
-            let mut sign : u128 = 0;
-            let mut combine : u128 = 0;
-            let mut pkagg : u128 = 0;
-            let mut verify : u128 = 0;
-
-            for i in 0..round{
-                print!("\rRunning round {}/{}", i, round);
-                io::stdout().flush().unwrap();
-
-                let (setup_, sign_, combine_, pkagg_, verify_) = blsms_nopop(&mut rng, benchmark);
-                setup += setup_;
-                sign += sign_;
-                combine += combine_;
-                pkagg += pkagg_;
-                verify += verify_;
-            }
+        if benchmark {
+            run_benchmark(round, || blsms_pop(&mut rng, benchmark))?;
+        } else {
+            blsms_pop(&mut rng, benchmark)?;
+        }
+    }
 
-            print!("\r{}{}", " ".repeat(30), "\r");
-            io::stdout().flush().unwrap();
+    println!();
 
+    Ok(())
+}
 
-            setup /= round;
-            sign /= round;
-            combine /= round;
-            pkagg /= round;
-            verify /= round;
-            
-            println!("Average timing for {} rounds:\n", round);
-            println!("Setup time taken  : {}ns", fmt_time(&setup));
-            println!("Signing time taken: {}ns", fmt_time(&sign));
-            println!("Combine time taken: {}ns", fmt_time(&combine));
-            println!("PK Agg time taken : {}ns", fmt_time(&pkagg));
-            println!("Verify time taken : {}ns", fmt_time(&verify));
-        }else{
-            blsms_nopop(&mut rng, benchmark);
-        }    
-    }
-
-    /*
-    * pair::g2mul() vs ecp2.mul() performance check
-    * g2mul() is faster for big multiplier
-    * mul() is faster for small multiplier
-    * break even is around 100-bit multiplier
-    *
-    let order = BIG::new_ints(&rom::CURVE_ORDER);
-    let mut time0 = Instant::now();
-    let mut num = BIG::new_int(10);
-    let mut g2 = pair::g2mul(&ECP2::generator(), &num);    
-    let mut tt = time0.elapsed().as_nanos();
-    
-    print!("g2 is  "); 
-    printbinary(&ecp2tobytes(&g2));
-    print!("num is "); 
-    printbinary(&bigtobytes(&num));
-    println!("g2mul time is: {}ns", fmt_time(&tt));
-
-    time0 = Instant::now();
-    g2 = ECP2::generator().mul(&num);
-    tt = time0.elapsed().as_nanos();
-    
-    print!("g2 is "); 
-    printbinary(&ecp2tobytes(&g2));
-    print!("num is "); 
-    printbinary(&bigtobytes(&num));
-    println!("mul time is: {}ns", fmt_time(&tt));
-
-    time0 = Instant::now();
-    num = BIG::randomnum(&order, &mut rng[0]);
-    g2 = pair::g2mul(&ECP2::generator(), &num);
-    tt = time0.elapsed().as_nanos();
-    
-    print!("g2 is "); 
-    printbinary(&ecp2tobytes(&g2));
-    print!("num is on "); 
-    printbinary(&bigtobytes(&num));
-    println!("g2mul time is: {}ns", fmt_time(&tt));
-
-    time0 = Instant::now();
-    g2 = ECP2::generator().mul(&num);
-    tt = time0.elapsed().as_nanos();
-    
-    print!("g2 is "); 
-    printbinary(&ecp2tobytes(&g2));
-    print!("num is "); 
-    printbinary(&bigtobytes(&num));
-    println!("mul time is: {}ns", fmt_time(&tt));
-    */
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    println!();
-}
\ No newline at end of file
+    match args.first().map(|s| s.as_str()) {
+        None | Some("bench") => {
+            if let Err(e) = bench() {
+                eprintln!("error: {}", e);
+                process::exit(2);
+            }
+        }
+        Some("bdn-scaling") => {
+            let max = match args.get(1).map(|n| n.parse::<usize>()) {
+                None => 10000,
+                Some(Ok(n)) if n > 0 => n,
+                Some(_) => {
+                    eprintln!("error: invalid number of signers '{}'", args[1]);
+                    process::exit(2);
+                }
+            };
+
+            if let Err(e) = bdn_scaling(max) {
+                eprintln!("error: {}", e);
+                process::exit(2);
+            }
+        }
+        Some("rogue-key") => {
+            let honest = match args.get(1).map(|n| n.parse::<usize>()) {
+                None => 4,
+                Some(Ok(n)) if n > 0 => n,
+                Some(_) => {
+                    eprintln!("error: invalid number of signers '{}'", args[1]);
+                    process::exit(2);
+                }
+            };
+
+            match rogue_key_demo(honest) {
+                Ok(true) => {}
+                Ok(false) => process::exit(1),
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    process::exit(2);
+                }
+            }
+        }
+        Some("forgery-lab") => {
+            let ell = match args.get(1).map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) if n <= forgery_lab::MAX_LAB_ELL => ChallengeLength::new(n).unwrap(),
+                _ => {
+                    eprintln!("error: forgery-lab needs an ell of at most {} bits", forgery_lab::MAX_LAB_ELL);
+                    process::exit(2);
+                }
+            };
+            let attempts = match args.get(2).map(|n| n.parse::<u64>()) {
+                None => 4 << ell.bits(),
+                Some(Ok(n)) if n > 0 => n,
+                Some(_) => {
+                    eprintln!("error: invalid number of attempts '{}'", args[2]);
+                    process::exit(2);
+                }
+            };
+
+            if let Err(e) = forgery_lab(ell, attempts) {
+                eprintln!("error: {}", e);
+                process::exit(2);
+            }
+        }
+        Some("batch-verify") => {
+            let count = match args.get(1).map(|n| n.parse::<usize>()) {
+                None => 100,
+                Some(Ok(n)) if n > 1 => n,
+                Some(_) => {
+                    eprintln!("error: invalid number of multisignatures '{}'", args[1]);
+                    process::exit(2);
+                }
+            };
+
+            if let Err(e) = batch_verify(count) {
+                eprintln!("error: {}", e);
+                process::exit(2);
+            }
+        }
+        Some("help") | Some("--help") | Some("-h") => println!("{}", cli::USAGE),
+        Some(cmd) => match cli::run(cmd, &args[1..]) {
+            Ok(true) => {}
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                eprintln!("error: {}\n\n{}", e, cli::USAGE);
+                process::exit(2);
+            }
+        },
+    }
+}
@@ -0,0 +1,172 @@
+//! Multi-scalar multiplication Σ k_i·P_i in G1 and G2 with Pippenger's bucket method.
+//!
+//! Each scalar is cut into c-bit windows. For every window the points are dropped into
+//! 2^c - 1 buckets by their digit, and the buckets are summed with the running-sum trick,
+//! so a window costs about n + 2^(c+1) additions instead of n scalar multiplications.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::ecp::ECP;
+use mcore::bls12381::ecp2::ECP2;
+
+trait Point: Clone {
+    fn identity() -> Self;
+    fn add_point(&mut self, other: &Self);
+    fn double(&mut self);
+}
+
+impl Point for ECP {
+    fn identity() -> Self {
+        ECP::new()
+    }
+
+    fn add_point(&mut self, other: &Self) {
+        self.add(other);
+    }
+
+    fn double(&mut self) {
+        self.dbl();
+    }
+}
+
+impl Point for ECP2 {
+    fn identity() -> Self {
+        ECP2::new()
+    }
+
+    fn add_point(&mut self, other: &Self) {
+        self.add(other);
+    }
+
+    fn double(&mut self) {
+        self.dbl();
+    }
+}
+
+/// Computes Σ scalars[i]·points[i] in G1. Both slices must have the same length.
+pub(crate) fn g1_msm(points: &[ECP], scalars: &[BIG]) -> ECP {
+    pippenger(points, scalars)
+}
+
+/// Computes Σ scalars[i]·points[i] in G2. Both slices must have the same length.
+pub(crate) fn g2_msm(points: &[ECP2], scalars: &[BIG]) -> ECP2 {
+    pippenger(points, scalars)
+}
+
+//window size in bits, roughly log2(n) as usual for Pippenger
+fn window_bits(n: usize) -> usize {
+    if n < 32 {
+        3
+    } else {
+        (usize::BITS - n.leading_zeros()) as usize * 69 / 100 + 2
+    }
+}
+
+//the c-bit digit of k starting at bit `start`
+fn digit(k: &BIG, start: usize, c: usize) -> usize {
+    let mut d = 0;
+
+    for j in (0..c).rev() {
+        d = (d << 1) | (k.bit(start + j) as usize);
+    }
+
+    d
+}
+
+fn pippenger<P: Point>(points: &[P], scalars: &[BIG]) -> P {
+    assert_eq!(points.len(), scalars.len(), "msm needs one scalar per point");
+
+    let mut result = P::identity();
+    let bits = scalars.iter().map(|k| k.nbits()).max().unwrap_or(0);
+    if bits == 0 {
+        return result;
+    }
+
+    let c = window_bits(points.len());
+    let windows = bits.div_ceil(c);
+    let mut buckets = vec![P::identity(); (1 << c) - 1];
+
+    for w in (0..windows).rev() {
+        for _ in 0..c {
+            result.double();
+        }
+
+        for b in buckets.iter_mut() {
+            *b = P::identity();
+        }
+        for (p, k) in points.iter().zip(scalars) {
+            let d = digit(k, w * c, c);
+            if d > 0 {
+                buckets[d - 1].add_point(p);
+            }
+        }
+
+        //Σ d·bucket_d as a sum of running sums from the top bucket down
+        let mut running = P::identity();
+        let mut sum = P::identity();
+        for b in buckets.iter().rev() {
+            running.add_point(b);
+            sum.add_point(&running);
+        }
+
+        result.add_point(&sum);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use mcore::bls12381::pair;
+    use mcore::bls12381::rom;
+
+    use super::*;
+    use crate::utils::test_rng;
+
+    //n random multiples of the generator with full-width scalars, every tenth scalar zero
+    fn inputs<P>(n: usize, generator: P, mul: fn(&P, &BIG) -> P) -> (Vec<P>, Vec<BIG>) {
+        let order = BIG::new_ints(&rom::CURVE_ORDER);
+        let mut rng = test_rng(8);
+
+        let points = (0..n).map(|_| mul(&generator, &BIG::randomnum(&order, &mut rng))).collect();
+        let scalars = (0..n)
+            .map(|i| if i % 10 == 9 { BIG::new() } else { BIG::randomnum(&order, &mut rng) })
+            .collect();
+
+        (points, scalars)
+    }
+
+    #[test]
+    fn g1_matches_naive_sum() {
+        //n = 40 takes the window size for n ≥ 32
+        for n in [1, 5, 40] {
+            let (points, scalars) = inputs(n, ECP::generator(), pair::g1mul);
+
+            let mut naive = ECP::new();
+            for (p, k) in points.iter().zip(&scalars) {
+                naive.add(&pair::g1mul(p, k));
+            }
+
+            assert!(g1_msm(&points, &scalars).equals(&naive), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn g2_matches_naive_sum() {
+        for n in [1, 5, 40] {
+            let (points, scalars) = inputs(n, ECP2::generator(), pair::g2mul);
+
+            let mut naive = ECP2::new();
+            for (p, k) in points.iter().zip(&scalars) {
+                naive.add(&pair::g2mul(p, k));
+            }
+
+            assert!(g2_msm(&points, &scalars).equals(&naive), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn empty_and_zero_sums_are_the_identity() {
+        assert!(g1_msm(&[], &[]).is_infinity());
+        assert!(g2_msm(&[ECP2::generator()], &[BIG::new()]).is_infinity());
+    }
+}
@@ -0,0 +1,84 @@
+//! Plain BLS multi-signature without proof-of-possession.
+//!
+//! Public keys are simply summed, which makes the scheme vulnerable to rogue-key attacks.
+//! It is only kept as a performance baseline.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::bls::bls_hash_to_point;
+use mcore::bls12381::ecp::ECP;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::pair;
+use mcore::rand::RAND;
+
+use crate::error::{Error, Result};
+use crate::partial::check_partials;
+use crate::types::{AggregatePublicKey, MultiSignature, PartialSignature, PublicKey, SecretKey};
+use crate::utils::bls_setup;
+
+/// Generates a signer key pair.
+pub fn keygen(rng: &mut RAND) -> Result<(SecretKey, PublicKey)> {
+    bls_setup(rng)
+}
+
+/// Produces a signer's partial signature on `m`.
+pub fn sign(sk: &SecretKey, m: &[u8]) -> Result<PartialSignature> {
+    Ok(PartialSignature(pair::g1mul(&bls_hash_to_point(m), &sk.0)))
+}
+
+/// Combines partial signatures by summing them.
+pub fn combine(sig: &[PartialSignature]) -> Result<MultiSignature> {
+    if sig.is_empty() {
+        return Err(Error::EmptySignerSet);
+    }
+
+    let mut sigma = ECP::new();
+
+    for s in sig {
+        sigma.add(&s.0);
+    }
+
+    Ok(MultiSignature(sigma))
+}
+
+/// As [`combine`], after checking each partial signature on `m` against the key at the
+/// same position in `pkvec`. Fails naming the signers whose partial signatures are invalid.
+pub fn combine_verified(sig: &[PartialSignature], pkvec: &[PublicKey], m: &[u8]) -> Result<MultiSignature> {
+    let keys: Vec<ECP2> = pkvec.iter().map(|pk| pk.0.clone()).collect();
+    check_partials(sig, &keys, &vec![BIG::new_int(1); keys.len()], &bls_hash_to_point(m))?;
+
+    combine(sig)
+}
+
+/// Aggregates public keys by summing them.
+pub fn aggregate_public_keys(pkvec: &[PublicKey]) -> Result<AggregatePublicKey> {
+    if pkvec.is_empty() {
+        return Err(Error::EmptySignerSet);
+    }
+
+    let mut apk = ECP2::new();
+
+    for pk in pkvec {
+        apk.add(&pk.0);
+    }
+
+    Ok(AggregatePublicKey(apk))
+}
+
+/// Checks e(sigma, g2) == e(H(m), apk).
+pub fn verify(sig: &MultiSignature, m: &[u8], apk: &AggregatePublicKey) -> Result<()> {
+    let mut s = sig.0.clone();
+    s.neg();
+
+    let mut r = pair::initmp();
+    pair::another(&mut r, &ECP2::generator(), &s);
+    pair::another(&mut r, &apk.0, &bls_hash_to_point(m));
+
+    let mut v = pair::miller(&mut r);
+    v = pair::fexp(&v);
+
+    if v.isunity() {
+        Ok(())
+    } else {
+        Err(Error::VerificationFailed)
+    }
+}
@@ -0,0 +1,234 @@
+//! Incremental OUR-MS-style aggregate signatures where every signer signs its own message.
+//!
+//! The first signer signs m_1 and produces S2 = sk_1·H(m_1) and the challenge c from S2 and
+//! m_1. Signer i, with its own message m_i, then folds sk_i·(S2 + (c + i)·H(m_i)) into S1,
+//! as in `our_ms::IncrementalSession`. The result verifies when
+//!
+//! e(S1 + S2, g2) = e(S2, K1) · Π_i e((c + i)·H(m_i), pk_i) · e(H(m_1), pk_1)
+//!
+//! with K1 = Σ pk_i; the verifier checks this with one multi-pairing of n + 2 pairings.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::bls::bls_hash_to_point;
+use mcore::bls12381::ecp::ECP;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::pair;
+use mcore::bls12381::rom;
+
+use crate::error::{Error, Result};
+use crate::our_ms::{ChallengeContext, ChallengeLength, OurMultiSignature};
+use crate::types::{PublicKey, SecretKey};
+
+/// A signer of an aggregate signature: its index, public key and message. The first entry
+/// of a list is the signer who started the session.
+pub type SignedMessage<'a> = (usize, &'a PublicKey, &'a [u8]);
+
+/// An aggregate signature being built by signers in turn, each on its own message.
+#[derive(Clone)]
+pub struct AggregateSession {
+    c: BIG,
+    sig: OurMultiSignature,
+    signers: usize,
+}
+
+impl AggregateSession {
+    /// Starts a session with the first signer (index 1) signing `m`, who produces S2.
+    pub fn start(first_signer: &SecretKey, m: &[u8], ell: ChallengeLength, ctx: &ChallengeContext) -> AggregateSession {
+        let s2 = pair::g1mul(&bls_hash_to_point(m), &first_signer.0);
+        let c = ctx.challenge(ell, &s2, m);
+
+        let mut session = AggregateSession {
+            c,
+            sig: OurMultiSignature { s1: ECP::new(), s2, ell },
+            signers: 0,
+        };
+        session.fold(first_signer, m);
+
+        session
+    }
+
+    /// Resumes a session from an intermediate aggregate signature started on `first_m`
+    /// that already contains `signers` contributions, rejecting it unless it was started
+    /// with `ell`.
+    pub fn resume(
+        sig: &OurMultiSignature,
+        first_m: &[u8],
+        signers: usize,
+        ell: ChallengeLength,
+        ctx: &ChallengeContext,
+    ) -> Result<AggregateSession> {
+        sig.check_ell(ell)?;
+
+        Ok(AggregateSession {
+            c: ctx.challenge(ell, &sig.s2, first_m),
+            sig: sig.clone(),
+            signers,
+        })
+    }
+
+    /// Folds in the contribution sk·(S2 + (c + index)·H(m)) of the signer at `index` on its
+    /// message `m`. Signers must append in index order, so `index` has to be
+    /// [`signers`](AggregateSession::signers) + 1.
+    pub fn append(&mut self, signer_key: &SecretKey, index: usize, m: &[u8]) -> Result<()> {
+        if index != self.signers + 1 {
+            return Err(Error::InvalidSignerIndex);
+        }
+
+        self.fold(signer_key, m);
+        Ok(())
+    }
+
+    /// Number of contributions folded in so far.
+    pub fn signers(&self) -> usize {
+        self.signers
+    }
+
+    /// The current intermediate aggregate signature.
+    pub fn current(&self) -> &OurMultiSignature {
+        &self.sig
+    }
+
+    pub fn finalize(self) -> OurMultiSignature {
+        self.sig
+    }
+
+    //the next signer's contribution, at index signers + 1
+    fn fold(&mut self, signer_key: &SecretKey, m: &[u8]) {
+        let mut t = pair::g1mul(&bls_hash_to_point(m), &weight(&self.c, self.signers + 1));
+        t.add(&self.sig.s2);
+        self.sig.s1.add(&pair::g1mul(&t, &signer_key.0));
+        self.signers += 1;
+    }
+}
+
+/// Verifies `sig` against its signers, the session's first signer (index 1) first, under
+/// the challenge length `ell`. Indices must be distinct.
+pub fn verify(sig: &OurMultiSignature, signers: &[SignedMessage], ell: ChallengeLength, ctx: &ChallengeContext) -> Result<()> {
+    let (first, _, first_m) = *signers.first().ok_or(Error::EmptySignerSet)?;
+    if first != 1 {
+        return Err(Error::InvalidSignerIndex);
+    }
+    sig.check_ell(ell)?;
+
+    let mut indices: Vec<usize> = signers.iter().map(|(i, _, _)| *i).collect();
+    indices.sort_unstable();
+    if indices.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::InvalidSignerIndex);
+    }
+
+    let c = ctx.challenge(ell, &sig.s2, first_m);
+
+    //e(S1 + S2, g2)^-1
+    let mut lhs = sig.s1.clone();
+    lhs.add(&sig.s2);
+    lhs.neg();
+    let mut r = pair::initmp();
+    pair::another(&mut r, &ECP2::generator(), &lhs);
+
+    //e(S2, K1)
+    let mut k1 = ECP2::new();
+    for (_, pk, _) in signers {
+        k1.add(&pk.0);
+    }
+    pair::another(&mut r, &k1, &sig.s2);
+
+    //e((c + i)·H(m_i), pk_i), the first signer's also covering e(H(m_1), pk_1)
+    for (n, (i, pk, m)) in signers.iter().enumerate() {
+        let hash_m = bls_hash_to_point(m);
+        let mut t = pair::g1mul(&hash_m, &weight(&c, *i));
+        if n == 0 {
+            t.add(&hash_m);
+        }
+        pair::another(&mut r, &pk.0, &t);
+    }
+
+    let mut v = pair::miller(&mut r);
+    v = pair::fexp(&v);
+
+    if v.isunity() {
+        Ok(())
+    } else {
+        Err(Error::VerificationFailed)
+    }
+}
+
+//c + index mod r
+fn weight(c: &BIG, index: usize) -> BIG {
+    let order = BIG::new_ints(&rom::CURVE_ORDER);
+
+    BIG::modadd(c, &BIG::new_int(index as isize), &order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::our_ms::{aggregate_public_keys, keygen};
+    use crate::utils::test_rng;
+
+    const MESSAGES: [&[u8]; 4] = [b"first", b"second", b"third", b"fourth"];
+
+    fn signed() -> (Vec<PublicKey>, OurMultiSignature, ChallengeLength, ChallengeContext) {
+        let mut rng = test_rng(22);
+        let (sk, pk): (Vec<SecretKey>, Vec<PublicKey>) = (0..4).map(|_| keygen(&mut rng).unwrap()).unzip();
+        let ell = ChallengeLength::new(128).unwrap();
+        let ctx = ChallengeContext::for_key(&aggregate_public_keys(&pk).unwrap());
+
+        let mut session = AggregateSession::start(&sk[0], MESSAGES[0], ell, &ctx);
+        for (i, sk_) in sk.iter().enumerate().skip(1) {
+            session.append(sk_, i + 1, MESSAGES[i]).unwrap();
+        }
+
+        (pk, session.finalize(), ell, ctx)
+    }
+
+    fn signers(pk: &[PublicKey]) -> Vec<SignedMessage<'_>> {
+        pk.iter().zip(MESSAGES).enumerate().map(|(i, (pk_, m))| (i + 1, pk_, m)).collect()
+    }
+
+    #[test]
+    fn verifies_valid_aggregate() {
+        let (pk, sig, ell, ctx) = signed();
+        assert_eq!(verify(&sig, &signers(&pk), ell, &ctx), Ok(()));
+    }
+
+    #[test]
+    fn rejects_wrong_message() {
+        let (pk, sig, ell, ctx) = signed();
+        let mut signers = signers(&pk);
+        signers[2].2 = b"something else";
+
+        assert_eq!(verify(&sig, &signers, ell, &ctx), Err(Error::VerificationFailed));
+    }
+
+    #[test]
+    fn rejects_duplicate_indices() {
+        let (pk, sig, ell, ctx) = signed();
+        let mut signers = signers(&pk);
+        signers[3].0 = 2;
+
+        assert_eq!(verify(&sig, &signers, ell, &ctx), Err(Error::InvalidSignerIndex));
+    }
+
+    #[test]
+    fn rejects_wrong_first_entry() {
+        let (pk, sig, ell, ctx) = signed();
+        let mut signers = signers(&pk);
+        signers.swap(0, 1);
+
+        assert_eq!(verify(&sig, &signers, ell, &ctx), Err(Error::InvalidSignerIndex));
+        assert_eq!(verify(&sig, &[], ell, &ctx), Err(Error::EmptySignerSet));
+    }
+
+    #[test]
+    fn append_rejects_out_of_order_index() {
+        let mut rng = test_rng(22);
+        let (sk, _) = keygen(&mut rng).unwrap();
+        let ctx = ChallengeContext::unbound();
+        let mut session = AggregateSession::start(&sk, MESSAGES[0], ChallengeLength::new(128).unwrap(), &ctx);
+
+        assert_eq!(session.append(&sk, 1, MESSAGES[1]), Err(Error::InvalidSignerIndex));
+        assert_eq!(session.append(&sk, 3, MESSAGES[1]), Err(Error::InvalidSignerIndex));
+        assert_eq!(session.signers(), 1);
+        assert_eq!(session.append(&sk, 2, MESSAGES[1]), Ok(()));
+    }
+}
@@ -0,0 +1,210 @@
+//! Batch verification of many OUR-MS multisignatures.
+//!
+//! Each multisignature j passes when
+//! e(S1_j + S2_j, g2)^-1 · e(S2_j + c_j·H(m_j), K1_j) · e(H(m_j), K2_j) = 1.
+//! Raising the j-th check to a random 64-bit r_j and multiplying them all merges the g2
+//! pairings into one, so a batch of k costs 2k + 1 Miller loops and a single final
+//! exponentiation instead of 3k loops and k exponentiations. A batch with an invalid entry
+//! passes with probability about 2^-64. If it fails, the batch is bisected to find the
+//! invalid entries. Entries made with another challenge length than their item's `ell` are
+//! invalid without any pairing.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::bls::bls_hash_to_point;
+use mcore::bls12381::ecp::ECP;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::pair;
+use mcore::bls12381::rom;
+use mcore::rand::RAND;
+
+use crate::our_ms::{ChallengeContext, ChallengeLength, OurAggregateKey, OurMultiSignature};
+
+/// Bit length of the random scalars.
+const BATCH_SCALAR_BITS: usize = 64;
+
+/// One multisignature to verify, with what `our_ms::verify` would take.
+#[derive(Clone, Copy)]
+pub struct BatchItem<'a> {
+    pub sig: &'a OurMultiSignature,
+    pub m: &'a [u8],
+    pub apk: &'a OurAggregateKey,
+    pub ell: ChallengeLength,
+    pub ctx: &'a ChallengeContext,
+}
+
+//an item with H(m) and c computed once, for all bisection rounds
+struct Prepared<'a> {
+    item: &'a BatchItem<'a>,
+    position: usize,
+    hash_m: ECP,
+    c: BIG,
+}
+
+/// Verifies all `items` at once. Returns the positions of the invalid ones, in ascending
+/// order; empty if every multisignature verifies.
+pub fn verify_batch(items: &[BatchItem], rng: &mut RAND) -> Vec<usize> {
+    let mut invalid = Vec::new();
+    let mut prepared: Vec<Prepared> = Vec::with_capacity(items.len());
+
+    for (position, item) in items.iter().enumerate() {
+        if item.sig.check_ell(item.ell).is_err() {
+            invalid.push(position);
+            continue;
+        }
+
+        let hash_m = bls_hash_to_point(item.m);
+        let c = item.ctx.challenge(item.ell, &item.sig.s2, item.m);
+        prepared.push(Prepared { item, position, hash_m, c });
+    }
+
+    bisect(&prepared, rng, &mut invalid);
+    invalid.sort_unstable();
+
+    invalid
+}
+
+fn bisect(items: &[Prepared], rng: &mut RAND, invalid: &mut Vec<usize>) {
+    if items.is_empty() || check(items, rng) {
+        return;
+    }
+    if items.len() == 1 {
+        invalid.push(items[0].position);
+        return;
+    }
+
+    let mid = items.len() / 2;
+    bisect(&items[..mid], rng, invalid);
+    bisect(&items[mid..], rng, invalid);
+}
+
+//the random linear combination of the checks of `items`, in one multi-pairing
+fn check(items: &[Prepared], rng: &mut RAND) -> bool {
+    #[cfg(test)]
+    tests::CHECKS.with(|n| n.set(n.get() + 1));
+
+    let order = BIG::new_ints(&rom::CURVE_ORDER);
+    let mut r = pair::initmp();
+
+    //Σ r_j·(S1_j + S2_j), paired with g2 once
+    let mut lhs = ECP::new();
+
+    for p in items {
+        let rj = BIG::randtrunc(&order, BATCH_SCALAR_BITS, rng);
+        let sig = p.item.sig;
+
+        let mut t = sig.s1.clone();
+        t.add(&sig.s2);
+        lhs.add(&pair::g1mul(&t, &rj));
+
+        //e(r_j·(S2_j + c_j·H(m_j)), K1_j)
+        let mut t = sig.s2.clone();
+        t.add(&pair::g1mul(&p.hash_m, &p.c));
+        pair::another(&mut r, &p.item.apk.k1, &pair::g1mul(&t, &rj));
+
+        //e(r_j·H(m_j), K2_j)
+        pair::another(&mut r, &p.item.apk.k2, &pair::g1mul(&p.hash_m, &rj));
+    }
+
+    lhs.neg();
+    pair::another(&mut r, &ECP2::generator(), &lhs);
+
+    let mut v = pair::miller(&mut r);
+    v = pair::fexp(&v);
+
+    v.isunity()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::our_ms::{aggregate_public_keys, combine, keygen, sign};
+    use crate::types::{PartialSignature, PublicKey, SecretKey};
+    use crate::utils::test_rng;
+
+    thread_local! {
+        //multi-pairing checks run on this thread
+        pub(super) static CHECKS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    struct Fixture {
+        messages: Vec<Vec<u8>>,
+        sigs: Vec<OurMultiSignature>,
+        apk: OurAggregateKey,
+        ell: ChallengeLength,
+        ctx: ChallengeContext,
+    }
+
+    fn fixture(count: usize) -> Fixture {
+        let mut rng = test_rng(20);
+        let (sk, pk): (Vec<SecretKey>, Vec<PublicKey>) = (0..3).map(|_| keygen(&mut rng).unwrap()).unzip();
+        let apk = aggregate_public_keys(&pk).unwrap();
+        let ell = ChallengeLength::new(128).unwrap();
+        let ctx = ChallengeContext::for_key(&apk);
+
+        let messages: Vec<Vec<u8>> = (0..count).map(|j| format!("message {}", j).into_bytes()).collect();
+        let sigs = messages
+            .iter()
+            .map(|m| {
+                let sig: Vec<PartialSignature> = sk.iter().map(|sk_| sign(sk_, m).unwrap()).collect();
+                combine(&sig, &sk[0], m, ell, &ctx).unwrap()
+            })
+            .collect();
+
+        Fixture { messages, sigs, apk, ell, ctx }
+    }
+
+    fn items(f: &Fixture) -> Vec<BatchItem<'_>> {
+        f.messages
+            .iter()
+            .zip(&f.sigs)
+            .map(|(m, sig)| BatchItem {
+                sig,
+                m,
+                apk: &f.apk,
+                ell: f.ell,
+                ctx: &f.ctx,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn valid_batch_has_no_invalid_entries() {
+        let f = fixture(8);
+        assert_eq!(verify_batch(&items(&f), &mut test_rng(1)), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn empty_batch_has_no_invalid_entries() {
+        assert_eq!(verify_batch(&[], &mut test_rng(1)), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn bisection_finds_every_invalid_entry() {
+        let f = fixture(8);
+        let mut items = items(&f);
+        //valid multisignatures on other messages
+        items[3].sig = &f.sigs[4];
+        items[6].sig = &f.sigs[0];
+
+        assert_eq!(verify_batch(&items, &mut test_rng(1)), vec![3, 6]);
+    }
+
+    #[test]
+    fn wrong_ell_is_invalid_without_pairing() {
+        let f = fixture(2);
+        let mut items = items(&f);
+        for item in &mut items {
+            item.ell = ChallengeLength::new(64).unwrap();
+        }
+
+        CHECKS.with(|n| n.set(0));
+        assert_eq!(verify_batch(&items, &mut test_rng(1)), vec![0, 1]);
+        assert_eq!(CHECKS.with(|n| n.get()), 0);
+
+        items[0].ell = f.ell;
+        assert_eq!(verify_batch(&items, &mut test_rng(1)), vec![1]);
+        assert_eq!(CHECKS.with(|n| n.get()), 1);
+    }
+}
@@ -0,0 +1,331 @@
+//! OUR-MS aggregated keys maintained under membership changes.
+//!
+//! K1 = Σ pk_i and K2 = Σ i·pk_i + pk_f are linear in the keys, so adding or removing the
+//! signer at index i only adds or subtracts pk_i and i·pk_i: constant time instead of the
+//! O(n) of `our_ms::aggregate_public_keys`. Removing a signer does not renumber the others,
+//! so after removals the state matches `our_subset::aggregate_public_keys` for the remaining
+//! members, and with members 1..=n it matches `our_ms::aggregate_public_keys`.
+//!
+//! The state keeps no key list, only K1, Σ i·pk_i, the first (lowest-index) member f and a
+//! bitmap of the indices still present. Callers must therefore pass each signer's registered
+//! key and index on removal, and name the next member when the first one leaves.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::pair;
+
+use crate::error::{Error, Result};
+use crate::our_ms::OurAggregateKey;
+use crate::types::{g2_from_bytes, g2_to_bytes, PublicKey};
+use crate::G2S;
+
+/// Size of an encoded [`AggregateKeyState`] with at least one member, before the bitmap of
+/// present indices.
+pub const STATE_SIZE: usize = 12 + 3 * G2S;
+
+/// The running (K1, K2) of a committee whose members come and go.
+#[derive(Clone)]
+pub struct AggregateKeyState {
+    //index the next added signer receives
+    next_index: usize,
+    members: usize,
+    //index i present iff bit (i − 1) % 8 of byte (i − 1) / 8 is set
+    present: Vec<u8>,
+    first: Option<(usize, PublicKey)>,
+    k1: ECP2,
+    //Σ i·pk_i, without the extra pk_f of K2
+    weighted: ECP2,
+}
+
+impl Default for AggregateKeyState {
+    fn default() -> AggregateKeyState {
+        AggregateKeyState::new()
+    }
+}
+
+impl AggregateKeyState {
+    /// A state without members; the first signer added gets index 1.
+    pub fn new() -> AggregateKeyState {
+        AggregateKeyState {
+            next_index: 1,
+            members: 0,
+            present: Vec::new(),
+            first: None,
+            k1: ECP2::new(),
+            weighted: ECP2::new(),
+        }
+    }
+
+    /// The state of the signers `pkvec` at indices 1..=n.
+    pub fn from_keys(pkvec: &[PublicKey]) -> AggregateKeyState {
+        let mut state = AggregateKeyState::new();
+        for pk in pkvec {
+            state.add_signer(pk);
+        }
+
+        state
+    }
+
+    /// Adds `pk` at the next index n + 1 and returns that index.
+    pub fn add_signer(&mut self, pk: &PublicKey) -> usize {
+        let index = self.next_index;
+
+        self.k1.add(&pk.0);
+        self.weighted.add(&weight(pk, index));
+        if self.first.is_none() {
+            self.first = Some((index, pk.clone()));
+        }
+
+        if self.present.len() < index.div_ceil(8) {
+            self.present.push(0);
+        }
+        self.present[(index - 1) / 8] |= 1 << ((index - 1) % 8);
+
+        self.members += 1;
+        self.next_index += 1;
+
+        index
+    }
+
+    /// Removes the member `pk` registered at `index`, which must be present and not the
+    /// first member.
+    ///
+    /// The state cannot tell whether `pk` is the key registered at `index`: passing any
+    /// other key succeeds and leaves (K1, K2) matching no set of members.
+    pub fn remove_signer(&mut self, pk: &PublicKey, index: usize) -> Result<()> {
+        match &self.first {
+            Some((f, _)) if index > *f && self.is_present(index) && self.members > 1 => {}
+            _ => return Err(Error::InvalidSignerIndex),
+        }
+
+        self.subtract(pk, index)
+    }
+
+    /// Removes the first member `pk` at `index`. `next` is the member with the lowest
+    /// remaining index, or `None` if `pk` was the only member.
+    ///
+    /// As with [`remove_signer`](AggregateKeyState::remove_signer), neither `pk` nor the
+    /// key in `next` is checked against its registered index.
+    pub fn remove_first_signer(&mut self, pk: &PublicKey, index: usize, next: Option<(&PublicKey, usize)>) -> Result<()> {
+        match (&self.first, next) {
+            (Some((f, _)), Some((_, n)))
+                if index == *f && n > index && self.is_present(n) && !(index + 1..n).any(|i| self.is_present(i)) && self.members > 1 => {}
+            (Some((f, _)), None) if index == *f && self.members == 1 => {}
+            _ => return Err(Error::InvalidSignerIndex),
+        }
+
+        self.subtract(pk, index)?;
+        self.first = next.map(|(pk_, n)| (n, pk_.clone()));
+
+        Ok(())
+    }
+
+    fn subtract(&mut self, pk: &PublicKey, index: usize) -> Result<()> {
+        self.members = self.members.checked_sub(1).ok_or(Error::EmptySignerSet)?;
+        self.present[(index - 1) / 8] &= !(1 << ((index - 1) % 8));
+        self.k1.sub(&pk.0);
+        self.weighted.sub(&weight(pk, index));
+
+        Ok(())
+    }
+
+    fn is_present(&self, index: usize) -> bool {
+        index > 0 && index < self.next_index && self.present[(index - 1) / 8] & (1 << ((index - 1) % 8)) != 0
+    }
+
+    /// The number of current members.
+    pub fn members(&self) -> usize {
+        self.members
+    }
+
+    /// The current (K1, K2).
+    pub fn aggregate_key(&self) -> Result<OurAggregateKey> {
+        let (_, first) = self.first.as_ref().ok_or(Error::EmptySignerSet)?;
+
+        let mut k2 = self.weighted.clone();
+        k2.add(&first.0);
+
+        Ok(OurAggregateKey {
+            k1: self.k1.clone(),
+            k2,
+        })
+    }
+
+    /// Decodes next index || members || first index, each 4 bytes big-endian, followed
+    /// for a non-empty state by pk_f || K1 || Σ i·pk_i || the bitmap of present indices
+    /// 1..next index.
+    pub fn from_bytes(b: &[u8]) -> Result<AggregateKeyState> {
+        if b.len() < 12 {
+            return Err(Error::InvalidPointEncoding);
+        }
+
+        let next_index = read_u32(&b[..4]);
+        let members = read_u32(&b[4..8]);
+        let first_index = read_u32(&b[8..12]);
+
+        if next_index == 0 || members >= next_index {
+            return Err(Error::InvalidSignerIndex);
+        }
+        if members == 0 {
+            return match (b.len(), first_index) {
+                (12, 0) => Ok(AggregateKeyState {
+                    next_index,
+                    present: vec![0; (next_index - 1).div_ceil(8)],
+                    ..AggregateKeyState::new()
+                }),
+                (12, _) => Err(Error::InvalidSignerIndex),
+                _ => Err(Error::InvalidPointEncoding),
+            };
+        }
+
+        if b.len() != STATE_SIZE + (next_index - 1).div_ceil(8) {
+            return Err(Error::InvalidPointEncoding);
+        }
+
+        //the bitmap must hold exactly `members` indices below next index, the lowest
+        //being the first member
+        let present = b[STATE_SIZE..].to_vec();
+        let indices: Vec<usize> = (1..next_index)
+            .filter(|i| present[(i - 1) / 8] & (1 << ((i - 1) % 8)) != 0)
+            .collect();
+        let set_bits: u32 = present.iter().map(|byte| byte.count_ones()).sum();
+        if indices.len() != members || set_bits as usize != members || indices[0] != first_index {
+            return Err(Error::InvalidSignerIndex);
+        }
+
+        Ok(AggregateKeyState {
+            next_index,
+            members,
+            present,
+            first: Some((first_index, PublicKey::from_bytes(&b[12..12 + G2S])?)),
+            k1: g2_from_bytes(&b[12 + G2S..12 + 2 * G2S])?,
+            weighted: g2_from_bytes(&b[12 + 2 * G2S..STATE_SIZE])?,
+        })
+    }
+
+    /// Encodes the state; see [`from_bytes`](AggregateKeyState::from_bytes).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out: Vec<u8> = Vec::with_capacity(STATE_SIZE + self.present.len());
+        out.extend((self.next_index as u32).to_be_bytes());
+        out.extend((self.members as u32).to_be_bytes());
+
+        match &self.first {
+            Some((f, pk)) => {
+                out.extend((*f as u32).to_be_bytes());
+                out.extend(pk.to_bytes());
+                out.extend(g2_to_bytes(&self.k1));
+                out.extend(g2_to_bytes(&self.weighted));
+                out.extend(&self.present);
+            }
+            None => out.extend(0u32.to_be_bytes()),
+        }
+
+        out
+    }
+}
+
+//index·pk
+fn weight(pk: &PublicKey, index: usize) -> ECP2 {
+    pair::g2mul(&pk.0, &BIG::new_int(index as isize))
+}
+
+fn read_u32(b: &[u8]) -> usize {
+    let mut n: [u8; 4] = [0; 4];
+    n.copy_from_slice(b);
+
+    u32::from_be_bytes(n) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::our_ms::keygen;
+    use crate::our_subset::{self, Participation};
+    use crate::utils::test_rng;
+
+    fn keys(n: usize) -> Vec<PublicKey> {
+        let mut rng = test_rng(19);
+        (0..n).map(|_| keygen(&mut rng).unwrap().1).collect()
+    }
+
+    #[test]
+    fn matches_subset_aggregate_after_removals() {
+        let pkvec = keys(10);
+        let mut state = AggregateKeyState::from_keys(&pkvec);
+        state.remove_signer(&pkvec[4], 5).unwrap();
+        state.remove_first_signer(&pkvec[0], 1, Some((&pkvec[1], 2))).unwrap();
+        state.remove_signer(&pkvec[9], 10).unwrap();
+
+        let participation = Participation::from_indices(10, &[2, 3, 4, 6, 7, 8, 9]).unwrap();
+        let expected = our_subset::aggregate_public_keys(&pkvec, &participation).unwrap();
+
+        assert_eq!(state.members(), 7);
+        assert_eq!(state.aggregate_key().unwrap().to_bytes(), expected.to_bytes());
+    }
+
+    #[test]
+    fn rejects_repeated_removal() {
+        let pkvec = keys(3);
+        let mut state = AggregateKeyState::from_keys(&pkvec);
+        state.remove_signer(&pkvec[1], 2).unwrap();
+
+        assert!(matches!(state.remove_signer(&pkvec[1], 2), Err(Error::InvalidSignerIndex)));
+        assert!(matches!(state.remove_first_signer(&pkvec[0], 1, Some((&pkvec[1], 2))), Err(Error::InvalidSignerIndex)));
+        assert_eq!(state.members(), 2);
+    }
+
+    #[test]
+    fn next_must_be_the_lowest_remaining_member() {
+        let pkvec = keys(4);
+        let mut state = AggregateKeyState::from_keys(&pkvec);
+
+        assert!(matches!(state.remove_first_signer(&pkvec[0], 1, Some((&pkvec[2], 3))), Err(Error::InvalidSignerIndex)));
+        assert_eq!(state.members(), 4);
+
+        state.remove_signer(&pkvec[1], 2).unwrap();
+        state.remove_first_signer(&pkvec[0], 1, Some((&pkvec[2], 3))).unwrap();
+
+        let participation = Participation::from_indices(4, &[3, 4]).unwrap();
+        let expected = our_subset::aggregate_public_keys(&pkvec, &participation).unwrap();
+        assert_eq!(state.aggregate_key().unwrap().to_bytes(), expected.to_bytes());
+    }
+
+    #[test]
+    fn rejects_removing_the_last_member() {
+        let pkvec = keys(2);
+        let mut state = AggregateKeyState::from_keys(&pkvec);
+        state.remove_signer(&pkvec[1], 2).unwrap();
+
+        assert!(matches!(state.remove_signer(&pkvec[0], 1), Err(Error::InvalidSignerIndex)));
+        assert!(matches!(state.remove_signer(&pkvec[1], 2), Err(Error::InvalidSignerIndex)));
+
+        state.remove_first_signer(&pkvec[0], 1, None).unwrap();
+        assert_eq!(state.members(), 0);
+        assert!(matches!(state.remove_first_signer(&pkvec[0], 1, None), Err(Error::InvalidSignerIndex)));
+    }
+
+    #[test]
+    fn round_trip_keeps_membership() {
+        let pkvec = keys(9);
+        let mut state = AggregateKeyState::from_keys(&pkvec);
+        state.remove_signer(&pkvec[3], 4).unwrap();
+
+        let bytes = state.to_bytes();
+        assert_eq!(bytes.len(), STATE_SIZE + 2);
+
+        let mut decoded = AggregateKeyState::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert!(matches!(decoded.remove_signer(&pkvec[3], 4), Err(Error::InvalidSignerIndex)));
+
+        //a bitmap disagreeing with the member count
+        let mut b = bytes.clone();
+        b[STATE_SIZE] |= 1 << 3;
+        assert!(matches!(AggregateKeyState::from_bytes(&b), Err(Error::InvalidSignerIndex)));
+
+        //a bit past the last index
+        let mut b = bytes;
+        b[STATE_SIZE] &= !(1 << 4);
+        b[STATE_SIZE + 1] |= 1 << 1;
+        assert!(matches!(AggregateKeyState::from_bytes(&b), Err(Error::InvalidSignerIndex)));
+    }
+}
@@ -0,0 +1,753 @@
+//! The fork-free BLS multi-signature scheme with incremental signing (OUR-MS).
+//!
+//! The multisignature is a pair (S1, S2) where S2 is the first signer's BLS signature and
+//! S1 = Σ (sk_1 + c + i)·σ_i with challenge c = H(ell, S2, context) truncated to `ell` bits,
+//! where the [`ChallengeContext`] holds a domain-separation tag and, optionally, the message,
+//! the signer set, the aggregated key and a session id. The aggregated key is the pair K1 = Σ pk_i,
+//! K2 = Σ i·pk_i + pk_1, which needs no scalar multiplications at all: Σ i·pk_i is the sum
+//! of the suffix sums Σ_{j≥i} pk_j.
+//!
+//! Index i is the position of the signer's key in `pkvec`. A [`SignerSet`] fixes that
+//! order independently of how keys and partial signatures arrive; see
+//! [`combine_in_set`].
+//!
+//! Since (sk_1 + c + i)·σ_i = sk_i·(S2 + (c + i)·H(m)), signer i can fold its contribution
+//! into S1 without knowing sk_1; [`IncrementalSession`] implements this.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::bls::bls_hash_to_point;
+use mcore::bls12381::ecp::ECP;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::pair;
+use mcore::bls12381::rom;
+use mcore::hash256::HASH256;
+use mcore::hmac;
+use mcore::rand::RAND;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::error::{Error, Result};
+use crate::partial::check_partials;
+use crate::signer_set::SignerSet;
+use crate::types::{g1_from_bytes, g1_to_bytes, g2_from_bytes, g2_to_bytes, PartialSignature, PublicKey, SecretKey};
+use crate::utils::bls_setup;
+#[cfg(feature = "parallel")]
+use crate::utils::chunk_len;
+use crate::{BGS, G1S, G2S};
+
+/// The bit length `ell` of the OUR-MS challenge c, from 0 up to the bit length of the
+/// group order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChallengeLength(usize);
+
+/// Default domain-separation tag of the OUR-MS challenge.
+pub const CHALLENGE_DST: &[u8] = b"BLS_INCSIGN_OURMS_BLS12381G1_XMD:SHA-256_CHALLENGE_";
+
+/// What the OUR-MS challenge is bound to besides ell and S2. Signers and verifier must use
+/// the same context; a signature produced under one context does not verify under another.
+///
+/// Unless the challenge depends on the signers' keys, colluding signers can choose their
+/// keys after computing c and forge with probability 1 at any ell (see `forgery_lab`).
+/// [`ChallengeContext::for_key`] binds the aggregated key and is the one to use.
+#[derive(Clone)]
+pub struct ChallengeContext {
+    dst: Vec<u8>,
+    message: bool,
+    signer_set: Option<[u8; 32]>,
+    aggregate_key: Option<[u8; 32]>,
+    session_id: Option<Vec<u8>>,
+}
+
+/// Size of an encoded [`OurMultiSignature`]: ell || S1 || S2.
+pub const OUR_SIGNATURE_SIZE: usize = 1 + 2 * G1S;
+
+/// An OUR-MS multisignature (S1, S2) together with the challenge length `ell` it was
+/// produced with.
+#[derive(Clone)]
+pub struct OurMultiSignature {
+    pub(crate) s1: ECP,
+    pub(crate) s2: ECP,
+    pub(crate) ell: ChallengeLength,
+}
+
+impl ChallengeLength {
+    /// The largest supported length, the bit length of the BLS12-381 group order.
+    pub const MAX: usize = 255;
+
+    pub fn new(bits: usize) -> Result<ChallengeLength> {
+        if bits > ChallengeLength::MAX {
+            return Err(Error::InvalidEll);
+        }
+
+        Ok(ChallengeLength(bits))
+    }
+
+    pub fn bits(&self) -> usize {
+        self.0
+    }
+}
+
+/// An OUR-MS aggregated public key (K1, K2).
+#[derive(Clone)]
+pub struct OurAggregateKey {
+    pub(crate) k1: ECP2,
+    pub(crate) k2: ECP2,
+}
+
+impl OurMultiSignature {
+    /// Decodes ell || S1 || S2.
+    pub fn from_bytes(b: &[u8]) -> Result<OurMultiSignature> {
+        if b.len() != OUR_SIGNATURE_SIZE {
+            return Err(Error::InvalidPointEncoding);
+        }
+
+        Ok(OurMultiSignature {
+            ell: ChallengeLength::new(b[0] as usize)?,
+            s1: g1_from_bytes(&b[1..1 + G1S])?,
+            s2: g1_from_bytes(&b[1 + G1S..])?,
+        })
+    }
+
+    /// Encodes the signature as ell || S1 || S2, ell taking one byte.
+    pub fn to_bytes(&self) -> [u8; OUR_SIGNATURE_SIZE] {
+        let mut out: [u8; OUR_SIGNATURE_SIZE] = [0; OUR_SIGNATURE_SIZE];
+        out[0] = self.ell.bits() as u8;
+        out[1..1 + G1S].copy_from_slice(&g1_to_bytes(&self.s1));
+        out[1 + G1S..].copy_from_slice(&g1_to_bytes(&self.s2));
+
+        out
+    }
+
+    pub fn ell(&self) -> ChallengeLength {
+        self.ell
+    }
+
+    //ell is the verifier's choice, never the signature's: with ell = 0 the challenge is 0
+    //whatever the context binds
+    pub(crate) fn check_ell(&self, ell: ChallengeLength) -> Result<()> {
+        if self.ell != ell {
+            return Err(Error::InvalidEll);
+        }
+
+        Ok(())
+    }
+}
+
+impl ChallengeContext {
+    /// A context binding only the application tag `dst`. Tags longer than 255 bytes are
+    /// hashed first, as in RFC 9380.
+    pub fn new(dst: &[u8]) -> ChallengeContext {
+        let dst = if dst.len() > 255 {
+            let mut h = HASH256::new();
+            h.process_array(b"H2C-OVERSIZE-DST-");
+            h.process_array(dst);
+            h.hash().to_vec()
+        } else {
+            dst.to_vec()
+        };
+
+        ChallengeContext {
+            dst,
+            message: false,
+            signer_set: None,
+            aggregate_key: None,
+            session_id: None,
+        }
+    }
+
+    /// The standard context: [`CHALLENGE_DST`], binding the message and the aggregated key
+    /// `apk` of the signers.
+    pub fn for_key(apk: &OurAggregateKey) -> ChallengeContext {
+        ChallengeContext::new(CHALLENGE_DST).bind_message().bind_aggregate_key(apk)
+    }
+
+    /// [`CHALLENGE_DST`] and the message only, leaving the signers' keys unbound. Only for
+    /// callers whose keys are fixed before any challenge can be computed, e.g. with proofs of
+    /// possession, or who bind them with [`bind_signer_set`](ChallengeContext::bind_signer_set).
+    pub fn unbound() -> ChallengeContext {
+        ChallengeContext::new(CHALLENGE_DST).bind_message()
+    }
+
+    /// Also binds the signed message.
+    pub fn bind_message(mut self) -> ChallengeContext {
+        self.message = true;
+        self
+    }
+
+    /// Also binds a commitment to the full signer set, in signer order.
+    pub fn bind_signer_set(mut self, pkvec: &[PublicKey]) -> ChallengeContext {
+        let mut h = HASH256::new();
+        for pk in pkvec {
+            h.process_array(&pk.to_bytes());
+        }
+
+        self.signer_set = Some(h.hash());
+        self
+    }
+
+    /// Also binds the aggregated key (K1, K2).
+    pub fn bind_aggregate_key(mut self, apk: &OurAggregateKey) -> ChallengeContext {
+        let mut h = HASH256::new();
+        h.process_array(&apk.to_bytes());
+
+        self.aggregate_key = Some(h.hash());
+        self
+    }
+
+    /// Also binds an application-chosen session id.
+    pub fn bind_session_id(mut self, id: &[u8]) -> ChallengeContext {
+        self.session_id = Some(id.to_vec());
+        self
+    }
+
+    //c = H(ell || S2 || [H(m)] || [signer set] || [aggregated key] || [session id]), each
+    //optional field preceded by a presence byte and the session id by its length
+    pub(crate) fn challenge(&self, ell: ChallengeLength, s2: &ECP, m: &[u8]) -> BIG {
+        let order = BIG::new_ints(&rom::CURVE_ORDER);
+
+        let mut input: Vec<u8> = Vec::with_capacity(1 + G1S + 4 + 96);
+        input.push(ell.bits() as u8);
+        input.extend(g1_to_bytes(s2));
+
+        if self.message {
+            let mut h = HASH256::new();
+            h.process_array(m);
+            input.push(1);
+            input.extend(h.hash());
+        } else {
+            input.push(0);
+        }
+
+        for commitment in [&self.signer_set, &self.aggregate_key] {
+            match commitment {
+                Some(commitment) => {
+                    input.push(1);
+                    input.extend(commitment);
+                }
+                None => input.push(0),
+            }
+        }
+
+        match &self.session_id {
+            Some(id) => {
+                input.push(1);
+                input.extend((id.len() as u32).to_be_bytes());
+                input.extend(id);
+            }
+            None => input.push(0),
+        }
+
+        hash_2big(&self.dst, &input, ell, &order)
+    }
+}
+
+impl OurAggregateKey {
+    /// Decodes K1 || K2.
+    pub fn from_bytes(b: &[u8]) -> Result<OurAggregateKey> {
+        if b.len() != 2 * G2S {
+            return Err(Error::InvalidPointEncoding);
+        }
+
+        Ok(OurAggregateKey {
+            k1: g2_from_bytes(&b[..G2S])?,
+            k2: g2_from_bytes(&b[G2S..])?,
+        })
+    }
+
+    /// Encodes the key as K1 || K2.
+    pub fn to_bytes(&self) -> [u8; 2 * G2S] {
+        let mut out: [u8; 2 * G2S] = [0; 2 * G2S];
+        out[..G2S].copy_from_slice(&g2_to_bytes(&self.k1));
+        out[G2S..].copy_from_slice(&g2_to_bytes(&self.k2));
+
+        out
+    }
+}
+
+/// A multisignature under construction, passed from signer to signer.
+///
+/// After signers with indices 1..=k have been folded in, the current state is a valid
+/// multisignature for the first k public keys.
+#[derive(Clone)]
+pub struct IncrementalSession {
+    hash_m: ECP,
+    c: BIG,
+    sig: OurMultiSignature,
+    signers: usize,
+}
+
+impl IncrementalSession {
+    /// Starts a session on `m` with the first signer (index 1), who produces S2.
+    pub fn start(first_signer: &SecretKey, m: &[u8], ell: ChallengeLength, ctx: &ChallengeContext) -> IncrementalSession {
+        let hash_m = bls_hash_to_point(m);
+        let s2 = pair::g1mul(&hash_m, &first_signer.0);
+        let c = ctx.challenge(ell, &s2, m);
+
+        let mut session = IncrementalSession {
+            hash_m,
+            c,
+            sig: OurMultiSignature { s1: ECP::new(), s2, ell },
+            signers: 0,
+        };
+        session.fold(first_signer);
+
+        session
+    }
+
+    /// Resumes a session from an intermediate multisignature on `m` that already
+    /// contains `signers` contributions, rejecting it unless it was started with `ell`.
+    pub fn resume(
+        sig: &OurMultiSignature,
+        m: &[u8],
+        signers: usize,
+        ell: ChallengeLength,
+        ctx: &ChallengeContext,
+    ) -> Result<IncrementalSession> {
+        sig.check_ell(ell)?;
+
+        Ok(IncrementalSession {
+            hash_m: bls_hash_to_point(m),
+            c: ctx.challenge(ell, &sig.s2, m),
+            sig: sig.clone(),
+            signers,
+        })
+    }
+
+    /// Folds in the contribution sk·(S2 + (c + index)·H(m)) of the signer at position
+    /// `index` (1-based) in the public-key list. Signers must append in index order, so
+    /// `index` has to be [`signers`](IncrementalSession::signers) + 1.
+    pub fn append(&mut self, signer_key: &SecretKey, index: usize) -> Result<()> {
+        if index != self.signers + 1 {
+            return Err(Error::InvalidSignerIndex);
+        }
+
+        self.fold(signer_key);
+        Ok(())
+    }
+
+    /// Number of contributions folded in so far.
+    pub fn signers(&self) -> usize {
+        self.signers
+    }
+
+    /// The current intermediate multisignature.
+    pub fn current(&self) -> &OurMultiSignature {
+        &self.sig
+    }
+
+    /// Verifies the intermediate state against the public keys of the signers folded in so far,
+    /// in index order.
+    pub fn verify(&self, pkvec: &[PublicKey]) -> Result<()> {
+        if pkvec.is_empty() {
+            return Err(Error::EmptySignerSet);
+        }
+        if pkvec.len() != self.signers {
+            return Err(Error::SignerSetMismatch);
+        }
+
+        verify_hashed(&self.sig, &self.hash_m, &self.c, &our_aggpk(pkvec))
+    }
+
+    pub fn finalize(self) -> OurMultiSignature {
+        self.sig
+    }
+
+    //adds sk·(S2 + (c + k)·H(m)) for the next index k
+    fn fold(&mut self, signer_key: &SecretKey) {
+        let order = BIG::new_ints(&rom::CURVE_ORDER);
+        let e = BIG::modadd(&self.c, &BIG::new_int((self.signers + 1) as isize), &order);
+
+        let mut t = pair::g1mul(&self.hash_m, &e);
+        t.add(&self.sig.s2);
+        self.sig.s1.add(&pair::g1mul(&t, &signer_key.0));
+        self.signers += 1;
+    }
+}
+
+/// Generates a signer key pair.
+pub fn keygen(rng: &mut RAND) -> Result<(SecretKey, PublicKey)> {
+    bls_setup(rng)
+}
+
+/// Produces a signer's partial signature on `m`.
+pub fn sign(sk: &SecretKey, m: &[u8]) -> Result<PartialSignature> {
+    Ok(PartialSignature(pair::g1mul(&bls_hash_to_point(m), &sk.0)))
+}
+
+/// Combines the partial signatures on `m` into (S1, S2), using the first signer's secret
+/// key `sk`. S2 is the first partial signature `sig[0]`.
+pub fn combine(
+    sig: &[PartialSignature],
+    sk: &SecretKey,
+    m: &[u8],
+    ell: ChallengeLength,
+    ctx: &ChallengeContext,
+) -> Result<OurMultiSignature> {
+    if sig.is_empty() {
+        return Err(Error::EmptySignerSet);
+    }
+
+    let order = BIG::new_ints(&rom::CURVE_ORDER);
+    let c = ctx.challenge(ell, &sig[0].0, m);
+
+    Ok(OurMultiSignature {
+        s1: our_combiner(sig, &sk.0, &order, &c),
+        s2: sig[0].0.clone(),
+        ell,
+    })
+}
+
+/// As [`combine`], after checking each partial signature on `m` against the key at the
+/// same position in `pkvec`. Fails naming the signers whose partial signatures are invalid.
+pub fn combine_verified(
+    sig: &[PartialSignature],
+    pkvec: &[PublicKey],
+    sk: &SecretKey,
+    m: &[u8],
+    ell: ChallengeLength,
+    ctx: &ChallengeContext,
+) -> Result<OurMultiSignature> {
+    let keys: Vec<ECP2> = pkvec.iter().map(|pk| pk.0.clone()).collect();
+    check_partials(sig, &keys, &vec![BIG::new_int(1); keys.len()], &bls_hash_to_point(m))?;
+
+    combine(sig, sk, m, ell, ctx)
+}
+
+/// As [`combine`], with the partial signatures in any order, each next to its signer's key,
+/// and placed by the signer's index in `set`. `sk` belongs to the set's first signer.
+pub fn combine_in_set(
+    set: &SignerSet,
+    sig: &[(PublicKey, PartialSignature)],
+    sk: &SecretKey,
+    m: &[u8],
+    ell: ChallengeLength,
+    ctx: &ChallengeContext,
+) -> Result<OurMultiSignature> {
+    if sig.len() != set.len() {
+        return Err(Error::SignerSetMismatch);
+    }
+
+    let mut slots: Vec<Option<PartialSignature>> = vec![None; set.len()];
+    for (pk, s) in sig {
+        match set.index_of(pk).map(|i| &mut slots[i - 1]) {
+            Some(slot @ None) => *slot = Some(s.clone()),
+            Some(Some(_)) => return Err(Error::DuplicateKey),
+            None => return Err(Error::SignerSetMismatch),
+        }
+    }
+
+    //n distinct members among n signatures, so every slot is filled
+    let ordered: Vec<PartialSignature> = slots.into_iter().flatten().collect();
+
+    combine(&ordered, sk, m, ell, ctx)
+}
+
+/// Aggregates public keys into (K1, K2) using about 2n G2 additions.
+pub fn aggregate_public_keys(pkvec: &[PublicKey]) -> Result<OurAggregateKey> {
+    if pkvec.is_empty() {
+        return Err(Error::EmptySignerSet);
+    }
+
+    Ok(our_aggpk(pkvec))
+}
+
+/// Aggregates public keys into (K1, K2) with one small-scalar multiplication per signer.
+/// Gives the same result as [`aggregate_public_keys`]; kept as a benchmark reference.
+pub fn aggregate_public_keys_mul(pkvec: &[PublicKey]) -> Result<OurAggregateKey> {
+    if pkvec.is_empty() {
+        return Err(Error::EmptySignerSet);
+    }
+
+    Ok(our_aggpk_mul(pkvec))
+}
+
+/// Checks e(S1 + S2, g2) == e(S2 + c·H(m), K1)·e(H(m), K2). Fails with
+/// [`Error::InvalidEll`] unless `sig` was made with the challenge length `ell` the verifier
+/// expects.
+pub fn verify(
+    sig: &OurMultiSignature,
+    m: &[u8],
+    apk: &OurAggregateKey,
+    ell: ChallengeLength,
+    ctx: &ChallengeContext,
+) -> Result<()> {
+    sig.check_ell(ell)?;
+
+    verify_hashed(sig, &bls_hash_to_point(m), &ctx.challenge(ell, &sig.s2, m), apk)
+}
+
+fn verify_hashed(sig: &OurMultiSignature, hash_m: &ECP, c: &BIG, apk: &OurAggregateKey) -> Result<()> {
+    //e(S1 + S2, g2)
+    let mut lhs = sig.s1.clone();
+    lhs.add(&sig.s2);
+    lhs.neg();
+    let mut r = pair::initmp();
+    pair::another(&mut r, &ECP2::generator(), &lhs);
+
+    //e(S2 + H(m)^{H(S_2)}, K1)
+    let mut temp = sig.s2.clone();
+    temp.add(&hash_m.mul(c));
+    pair::another(&mut r, &apk.k1, &temp);
+
+    //e(H(m), K2)
+    pair::another(&mut r, &apk.k2, hash_m);
+
+    let mut v = pair::miller(&mut r);
+    v = pair::fexp(&v);
+
+    if v.isunity() {
+        Ok(())
+    } else {
+        Err(Error::VerificationFailed)
+    }
+}
+
+//(Σ σ_i, Σ i·σ_i) with i from 1, where Σ i·σ_i is the sum of the suffix sums
+pub(crate) fn range_sums(sig: &[PartialSignature]) -> (ECP, ECP) {
+    let mut suffix = ECP::new();
+    let mut weighted = ECP::new();
+
+    for s in sig.iter().rev() {
+        suffix.add(&s.0);
+        weighted.add(&suffix);
+    }
+
+    (suffix, weighted)
+}
+
+//Σ (sk + start + i)·σ_i = (sk + start)·Σ σ_i + Σ i·σ_i, so only one full-width
+//multiplication is needed
+fn combine_offset(sig: &[PartialSignature], sk: &BIG, order: &BIG, start: &BIG) -> ECP {
+    let (sum, weighted) = range_sums(sig);
+
+    let mut sigma = pair::g1mul(&sum, &BIG::modadd(sk, start, order));
+    sigma.add(&weighted);
+
+    sigma
+}
+
+#[cfg(not(feature = "parallel"))]
+fn our_combiner(sig: &[PartialSignature], sk: &BIG, order: &BIG, start: &BIG) -> ECP {
+    combine_offset(sig, sk, order, start)
+}
+
+//the chunk at offset a is a range of its own, starting at start + a
+#[cfg(feature = "parallel")]
+fn our_combiner(sig: &[PartialSignature], sk: &BIG, order: &BIG, start: &BIG) -> ECP {
+    let size = chunk_len(sig.len());
+
+    sig.par_chunks(size)
+        .enumerate()
+        .map(|(j, chunk)| {
+            let offset = BIG::modadd(start, &BIG::new_int((j * size) as isize), order);
+            combine_offset(chunk, sk, order, &offset)
+        })
+        .reduce(ECP::new, |mut a, b| {
+            a.add(&b);
+            a
+        })
+}
+
+//(Σ pk_i, Σ i·pk_i), accumulating the suffix sums Σ_{j≥i} pk_j from the last signer down;
+//the final suffix sum is the first
+fn weighted_sums(pkvec: &[PublicKey]) -> (ECP2, ECP2) {
+    let mut suffix = ECP2::new();
+    let mut weighted = ECP2::new();
+
+    for pk in pkvec.iter().rev() {
+        suffix.add(&pk.0);
+        weighted.add(&suffix);
+    }
+
+    (suffix, weighted)
+}
+
+#[cfg(not(feature = "parallel"))]
+fn our_aggpk(pkvec: &[PublicKey]) -> OurAggregateKey {
+    let (k1, mut k2) = weighted_sums(pkvec);
+    k2.add(&pkvec[0].0);
+
+    OurAggregateKey { k1, k2 }
+}
+
+//the chunk at offset a adds Σ (a + i)·pk_i = a·Σ pk_i + Σ i·pk_i
+#[cfg(feature = "parallel")]
+fn our_aggpk(pkvec: &[PublicKey]) -> OurAggregateKey {
+    let size = chunk_len(pkvec.len());
+
+    let (k1, mut k2) = pkvec
+        .par_chunks(size)
+        .enumerate()
+        .map(|(j, chunk)| {
+            let (sum, mut weighted) = weighted_sums(chunk);
+            if j > 0 {
+                weighted.add(&pair::g2mul(&sum, &BIG::new_int((j * size) as isize)));
+            }
+            (sum, weighted)
+        })
+        .reduce(
+            || (ECP2::new(), ECP2::new()),
+            |(mut a1, mut a2), (b1, b2)| {
+                a1.add(&b1);
+                a2.add(&b2);
+                (a1, a2)
+            },
+        );
+
+    k2.add(&pkvec[0].0);
+
+    OurAggregateKey { k1, k2 }
+}
+
+fn our_aggpk_mul(pkvec: &[PublicKey]) -> OurAggregateKey {
+    let mut k1 = ECP2::new();
+    let mut k2 = ECP2::new();
+
+    for (i, pk) in pkvec.iter().enumerate() {
+        k2.add(&pk.0.mul(&BIG::new_int((i as isize) + 1)));
+        k1.add(&pk.0);
+    }
+
+    k2.add(&pkvec[0].0);
+
+    OurAggregateKey { k1, k2 }
+}
+
+//the first ⌈ell/8⌉ bytes of expand_message_xmd(dst, input) as a big-endian integer,
+//keeping its low `ell` bits
+fn hash_2big(dst: &[u8], input: &[u8], ell: ChallengeLength, order: &BIG) -> BIG {
+    let bits = ell.bits();
+    if bits == 0 {
+        return BIG::new();
+    }
+
+    let mut output: [u8; 32] = [0; 32];
+    hmac::xmd_expand(hmac::MC_SHA2, 32, &mut output, 32, dst, input);
+
+    //BIG::frombytes reads BGS bytes; clear the bits of the leading byte beyond ell
+    let len = bits.div_ceil(8);
+    let mut padded: [u8; BGS] = [0; BGS];
+    padded[BGS - len..].copy_from_slice(&output[..len]);
+    padded[BGS - len] &= 0xFF >> (8 * len - bits);
+
+    let mut temp = BIG::frombytes(&padded);
+    temp.rmod(order);
+    temp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_rng;
+
+    const M: &[u8] = b"incremental";
+
+    fn keys(n: usize) -> (Vec<SecretKey>, Vec<PublicKey>) {
+        let mut rng = test_rng(3);
+        (0..n).map(|_| keygen(&mut rng).unwrap()).unzip()
+    }
+
+    #[test]
+    fn incremental_session_matches_combine() {
+        let (sk, pk) = keys(4);
+        let ell = ChallengeLength::new(128).unwrap();
+        let ctx = ChallengeContext::for_key(&aggregate_public_keys(&pk).unwrap());
+
+        let mut session = IncrementalSession::start(&sk[0], M, ell, &ctx);
+        for (i, sk_) in sk.iter().enumerate().skip(1) {
+            session.append(sk_, i + 1).unwrap();
+            assert_eq!(session.verify(&pk[..=i]), Ok(()));
+        }
+
+        let sig: Vec<PartialSignature> = sk.iter().map(|sk_| sign(sk_, M).unwrap()).collect();
+        let combined = combine(&sig, &sk[0], M, ell, &ctx).unwrap();
+        assert_eq!(session.finalize().to_bytes(), combined.to_bytes());
+    }
+
+    #[test]
+    fn append_rejects_out_of_order_index() {
+        let (sk, pk) = keys(3);
+        let ctx = ChallengeContext::for_key(&aggregate_public_keys(&pk).unwrap());
+        let mut session = IncrementalSession::start(&sk[0], M, ChallengeLength::new(128).unwrap(), &ctx);
+
+        assert_eq!(session.append(&sk[2], 3), Err(Error::InvalidSignerIndex));
+        assert_eq!(session.append(&sk[1], 1), Err(Error::InvalidSignerIndex));
+        assert_eq!(session.signers(), 1);
+        assert_eq!(session.append(&sk[1], 2), Ok(()));
+    }
+
+    #[test]
+    fn session_verify_rejects_key_count_mismatch() {
+        let (sk, pk) = keys(3);
+        let ctx = ChallengeContext::for_key(&aggregate_public_keys(&pk).unwrap());
+        let mut session = IncrementalSession::start(&sk[0], M, ChallengeLength::new(128).unwrap(), &ctx);
+        session.append(&sk[1], 2).unwrap();
+
+        assert_eq!(session.verify(&pk), Err(Error::SignerSetMismatch));
+        assert_eq!(session.verify(&pk[..1]), Err(Error::SignerSetMismatch));
+        assert_eq!(session.verify(&[]), Err(Error::EmptySignerSet));
+    }
+
+    //rogue keys Y1, Y2 after the honest keys with K1 = z·g2 and K2 = w·g2; with c = 0,
+    //(S2, S1) = (t·H(m), (tz + w − t)·H(m)) verifies
+    #[test]
+    fn verifier_fixes_ell() {
+        let (_, mut pk) = keys(3);
+        let order = BIG::new_ints(&rom::CURVE_ORDER);
+        let mut rng = test_rng(4);
+        let (z, w, t) = (BIG::randomnum(&order, &mut rng), BIG::randomnum(&order, &mut rng), BIG::randomnum(&order, &mut rng));
+
+        let honest = aggregate_public_keys(&pk).unwrap();
+        let n = pk.len() + 2;
+        let zg = pair::g2mul(&ECP2::generator(), &z);
+
+        //Y2 = w·g2 − B − (n − 1)·(z·g2 − A), Y1 = z·g2 − A − Y2
+        let mut za = zg.clone();
+        za.sub(&honest.k1);
+        let mut y2 = pair::g2mul(&ECP2::generator(), &w);
+        y2.sub(&honest.k2);
+        y2.sub(&pair::g2mul(&za, &BIG::new_int((n - 1) as isize)));
+        let mut y1 = za;
+        y1.sub(&y2);
+        pk.push(PublicKey(y1));
+        pk.push(PublicKey(y2));
+
+        let hash_m = bls_hash_to_point(M);
+        let mut e = BIG::modmul(&t, &z, &order);
+        e = BIG::modadd(&e, &w, &order);
+        e = BIG::modadd(&e, &BIG::modneg(&t, &order), &order);
+        let zero = ChallengeLength::new(0).unwrap();
+        let forgery = OurMultiSignature {
+            s1: pair::g1mul(&hash_m, &e),
+            s2: pair::g1mul(&hash_m, &t),
+            ell: zero,
+        };
+
+        let apk = aggregate_public_keys(&pk).unwrap();
+        let ctx = ChallengeContext::for_key(&apk);
+        assert_eq!(verify(&forgery, M, &apk, zero, &ctx), Ok(()));
+        assert_eq!(verify(&forgery, M, &apk, ChallengeLength::new(128).unwrap(), &ctx), Err(Error::InvalidEll));
+    }
+
+    //four uneven chunks of 3, 3, 3 and 1 signers
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_matches_sequential() {
+        use crate::utils::with_threads;
+
+        let (sk, pk) = keys(10);
+        let sig: Vec<PartialSignature> = sk.iter().map(|sk_| sign(sk_, M).unwrap()).collect();
+        let order = BIG::new_ints(&rom::CURVE_ORDER);
+        let start = BIG::new_int(5);
+
+        let (sigma, apk) = with_threads(4, || (our_combiner(&sig, &sk[0].0, &order, &start), our_aggpk(&pk)));
+
+        assert!(sigma.equals(&combine_offset(&sig, &sk[0].0, &order, &start)));
+
+        let (k1, mut k2) = weighted_sums(&pk);
+        k2.add(&pk[0].0);
+        assert!(apk.k1.equals(&k1));
+        assert!(apk.k2.equals(&k2));
+        assert_eq!(apk.to_bytes(), our_aggpk_mul(&pk).to_bytes());
+    }
+}
@@ -0,0 +1,127 @@
+//! OUR-MS verification with the G2 side of the pairings precomputed.
+//!
+//! Verification pairs against three G2 points: the generator, K1 and K2. For a fixed
+//! committee these never change, so [`PreparedVerifyingKey`] runs the G2 part of the Miller
+//! loop once with `pair::precomp` and keeps its line functions; each verification then only
+//! evaluates them at its G1 points with `pair::another_pc`.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::bls::bls_hash_to_point;
+use mcore::bls12381::ecp;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::fp4::FP4;
+use mcore::bls12381::pair;
+
+use crate::error::{Error, Result};
+use crate::our_ms::{ChallengeContext, ChallengeLength, OurAggregateKey, OurMultiSignature};
+
+/// Line functions of the generator, K1 and K2 of one aggregated key.
+#[derive(Clone)]
+pub struct PreparedVerifyingKey {
+    g2: Vec<FP4>,
+    k1: Vec<FP4>,
+    k2: Vec<FP4>,
+}
+
+impl PreparedVerifyingKey {
+    /// Precomputes the line functions for `apk`; worth it from the second verification on.
+    pub fn new(apk: &OurAggregateKey) -> PreparedVerifyingKey {
+        PreparedVerifyingKey {
+            g2: lines(&ECP2::generator()),
+            k1: lines(&apk.k1),
+            k2: lines(&apk.k2),
+        }
+    }
+
+    /// As `our_ms::verify`, under the prepared key.
+    pub fn verify(&self, sig: &OurMultiSignature, m: &[u8], ell: ChallengeLength, ctx: &ChallengeContext) -> Result<()> {
+        sig.check_ell(ell)?;
+
+        let hash_m = bls_hash_to_point(m);
+        let c: BIG = ctx.challenge(ell, &sig.s2, m);
+
+        //e(S1 + S2, g2)^-1
+        let mut lhs = sig.s1.clone();
+        lhs.add(&sig.s2);
+        lhs.neg();
+        let mut r = pair::initmp();
+        pair::another_pc(&mut r, &self.g2, &lhs);
+
+        //e(S2 + c·H(m), K1)
+        let mut temp = sig.s2.clone();
+        temp.add(&hash_m.mul(&c));
+        pair::another_pc(&mut r, &self.k1, &temp);
+
+        //e(H(m), K2)
+        pair::another_pc(&mut r, &self.k2, &hash_m);
+
+        let mut v = pair::miller(&mut r);
+        v = pair::fexp(&v);
+
+        if v.isunity() {
+            Ok(())
+        } else {
+            Err(Error::VerificationFailed)
+        }
+    }
+}
+
+fn lines(q: &ECP2) -> Vec<FP4> {
+    let mut q = q.clone();
+    q.affine();
+
+    let mut table = vec![FP4::new(); ecp::G2_TABLE];
+    pair::precomp(&mut table, &q);
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::our_ms::{self, aggregate_public_keys, combine, keygen, sign};
+    use crate::types::{PartialSignature, PublicKey, SecretKey};
+    use crate::utils::test_rng;
+
+    const M: &[u8] = b"prepared";
+
+    fn signed() -> (OurAggregateKey, OurMultiSignature, ChallengeLength, ChallengeContext) {
+        let mut rng = test_rng(21);
+        let (sk, pk): (Vec<SecretKey>, Vec<PublicKey>) = (0..4).map(|_| keygen(&mut rng).unwrap()).unzip();
+        let apk = aggregate_public_keys(&pk).unwrap();
+        let ell = ChallengeLength::new(128).unwrap();
+        let ctx = ChallengeContext::for_key(&apk);
+
+        let sig: Vec<PartialSignature> = sk.iter().map(|sk_| sign(sk_, M).unwrap()).collect();
+        let sigma = combine(&sig, &sk[0], M, ell, &ctx).unwrap();
+
+        (apk, sigma, ell, ctx)
+    }
+
+    #[test]
+    fn agrees_with_our_ms_verify() {
+        let (apk, sigma, ell, ctx) = signed();
+        let prepared = PreparedVerifyingKey::new(&apk);
+        let other_ell = ChallengeLength::new(64).unwrap();
+
+        for (m, ell, expected) in [
+            (M, ell, Ok(())),
+            (&b"another message"[..], ell, Err(Error::VerificationFailed)),
+            (M, other_ell, Err(Error::InvalidEll)),
+        ] {
+            assert_eq!(our_ms::verify(&sigma, m, &apk, ell, &ctx), expected);
+            assert_eq!(prepared.verify(&sigma, m, ell, &ctx), expected);
+        }
+    }
+
+    #[test]
+    fn rejects_another_key() {
+        let (apk, sigma, ell, ctx) = signed();
+        let mut rng = test_rng(2);
+        let (_, pk) = keygen(&mut rng).unwrap();
+        let other = aggregate_public_keys(&[pk]).unwrap();
+
+        assert_eq!(PreparedVerifyingKey::new(&apk).verify(&sigma, M, ell, &ctx), Ok(()));
+        assert_eq!(PreparedVerifyingKey::new(&other).verify(&sigma, M, ell, &ctx), Err(Error::VerificationFailed));
+    }
+}
@@ -0,0 +1,277 @@
+//! Combining OUR-MS partial signatures in independent contiguous ranges.
+//!
+//! S1 = Σ (sk_1 + c + i)·σ_i = sk_1·Σ σ_i + Σ (c + i)·σ_i. Both sums split over any
+//! partition of the signers into contiguous index ranges, and neither needs sk_1, only
+//! c, which follows from S2 = σ_1. Each range can therefore be combined on a different
+//! machine with [`combine_range`], the [`RangeCombination`]s merged with [`merge_ranges`]
+//! in any grouping, and the first signer finally applies its key with [`finish`]. The
+//! result equals `our_ms::combine` on all partial signatures at once.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::ecp::ECP;
+use mcore::bls12381::pair;
+use mcore::bls12381::rom;
+
+use crate::error::{Error, Result};
+use crate::our_ms::{range_sums, ChallengeContext, ChallengeLength, OurMultiSignature};
+use crate::types::{g1_from_bytes, g1_to_bytes, PartialSignature, SecretKey};
+use crate::G1S;
+
+/// Size of an encoded [`RangeCombination`].
+pub const RANGE_SIZE: usize = 9 + 3 * G1S;
+
+/// The combined partial signatures of the signers first..first + len.
+#[derive(Clone)]
+pub struct RangeCombination {
+    first: usize,
+    len: usize,
+    ell: ChallengeLength,
+    s2: ECP,
+    //Σ σ_i
+    sum: ECP,
+    //Σ (c + i)·σ_i
+    weighted: ECP,
+}
+
+impl RangeCombination {
+    /// The 1-based index of the first signer in the range.
+    pub fn first(&self) -> usize {
+        self.first
+    }
+
+    /// The number of signers in the range.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Always false; a range has at least one signer.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Decodes first || len, 4 bytes big-endian each, || ell || S2 || Σ σ_i || Σ (c + i)·σ_i.
+    pub fn from_bytes(b: &[u8]) -> Result<RangeCombination> {
+        if b.len() != RANGE_SIZE {
+            return Err(Error::InvalidPointEncoding);
+        }
+
+        let mut first: [u8; 4] = [0; 4];
+        let mut len: [u8; 4] = [0; 4];
+        first.copy_from_slice(&b[..4]);
+        len.copy_from_slice(&b[4..8]);
+
+        let first = u32::from_be_bytes(first) as usize;
+        let len = u32::from_be_bytes(len) as usize;
+        if first == 0 || len == 0 {
+            return Err(Error::InvalidSignerIndex);
+        }
+
+        Ok(RangeCombination {
+            first,
+            len,
+            ell: ChallengeLength::new(b[8] as usize)?,
+            s2: g1_from_bytes(&b[9..9 + G1S])?,
+            sum: g1_from_bytes(&b[9 + G1S..9 + 2 * G1S])?,
+            weighted: g1_from_bytes(&b[9 + 2 * G1S..])?,
+        })
+    }
+
+    /// Encodes the range; see [`from_bytes`](RangeCombination::from_bytes).
+    pub fn to_bytes(&self) -> [u8; RANGE_SIZE] {
+        let mut out: [u8; RANGE_SIZE] = [0; RANGE_SIZE];
+        out[..4].copy_from_slice(&(self.first as u32).to_be_bytes());
+        out[4..8].copy_from_slice(&(self.len as u32).to_be_bytes());
+        out[8] = self.ell.bits() as u8;
+        out[9..9 + G1S].copy_from_slice(&g1_to_bytes(&self.s2));
+        out[9 + G1S..9 + 2 * G1S].copy_from_slice(&g1_to_bytes(&self.sum));
+        out[9 + 2 * G1S..].copy_from_slice(&g1_to_bytes(&self.weighted));
+
+        out
+    }
+}
+
+/// Combines the partial signatures `sig` of the signers at indices first, first + 1, ...
+/// on `m`. `s2` is the first signer's partial signature, which fixes the challenge; a range
+/// starting at index 1 must begin with it.
+pub fn combine_range(
+    sig: &[PartialSignature],
+    first: usize,
+    s2: &PartialSignature,
+    m: &[u8],
+    ell: ChallengeLength,
+    ctx: &ChallengeContext,
+) -> Result<RangeCombination> {
+    if sig.is_empty() {
+        return Err(Error::EmptySignerSet);
+    }
+    if first == 0 {
+        return Err(Error::InvalidSignerIndex);
+    }
+    if first == 1 && !sig[0].0.equals(&s2.0) {
+        return Err(Error::SignerSetMismatch);
+    }
+
+    let order = BIG::new_ints(&rom::CURVE_ORDER);
+    let c = ctx.challenge(ell, &s2.0, m);
+
+    //Σ (c + first − 1 + k)·σ_k over the range's own positions k from 1
+    let (sum, mut weighted) = range_sums(sig);
+    let offset = BIG::modadd(&c, &BIG::new_int((first - 1) as isize), &order);
+    weighted.add(&pair::g1mul(&sum, &offset));
+
+    Ok(RangeCombination {
+        first,
+        len: sig.len(),
+        ell,
+        s2: s2.0.clone(),
+        sum,
+        weighted,
+    })
+}
+
+/// Merges ranges, given in any order, that together form one contiguous range.
+pub fn merge_ranges(ranges: &[RangeCombination]) -> Result<RangeCombination> {
+    let mut sorted: Vec<&RangeCombination> = ranges.iter().collect();
+    sorted.sort_by_key(|r| r.first);
+
+    let (head, rest) = sorted.split_first().ok_or(Error::EmptySignerSet)?;
+    let mut merged = (*head).clone();
+
+    for r in rest {
+        if r.first != merged.first + merged.len {
+            return Err(Error::InvalidSignerIndex);
+        }
+        if r.ell != merged.ell {
+            return Err(Error::InvalidEll);
+        }
+        if !r.s2.equals(&merged.s2) {
+            return Err(Error::SignerSetMismatch);
+        }
+
+        merged.len += r.len;
+        merged.sum.add(&r.sum);
+        merged.weighted.add(&r.weighted);
+    }
+
+    Ok(merged)
+}
+
+/// Turns a range covering all signers, from index 1, into the multisignature, using the
+/// first signer's secret key `sk`.
+pub fn finish(range: &RangeCombination, sk: &SecretKey) -> Result<OurMultiSignature> {
+    if range.first != 1 {
+        return Err(Error::InvalidSignerIndex);
+    }
+
+    let mut s1 = pair::g1mul(&range.sum, &sk.0);
+    s1.add(&range.weighted);
+
+    Ok(OurMultiSignature {
+        s1,
+        s2: range.s2.clone(),
+        ell: range.ell,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::our_ms::{self, aggregate_public_keys, keygen, sign, OUR_SIGNATURE_SIZE};
+    use crate::types::PublicKey;
+    use crate::utils::test_rng;
+
+    const M: &[u8] = b"ranges";
+
+    struct Fixture {
+        sk: Vec<SecretKey>,
+        sig: Vec<PartialSignature>,
+        ell: ChallengeLength,
+        ctx: ChallengeContext,
+    }
+
+    fn fixture(n: usize) -> Fixture {
+        let mut rng = test_rng(25);
+        let (sk, pk): (Vec<SecretKey>, Vec<PublicKey>) = (0..n).map(|_| keygen(&mut rng).unwrap()).unzip();
+        let sig = sk.iter().map(|sk_| sign(sk_, M).unwrap()).collect();
+
+        Fixture {
+            sk,
+            sig,
+            ell: ChallengeLength::new(128).unwrap(),
+            ctx: ChallengeContext::for_key(&aggregate_public_keys(&pk).unwrap()),
+        }
+    }
+
+    //one range per (first, len), encoded and decoded as if sent between machines
+    fn ranges(f: &Fixture, bounds: &[(usize, usize)]) -> Vec<RangeCombination> {
+        bounds
+            .iter()
+            .map(|&(first, len)| {
+                let range = combine_range(&f.sig[first - 1..first - 1 + len], first, &f.sig[0], M, f.ell, &f.ctx).unwrap();
+                RangeCombination::from_bytes(&range.to_bytes()).unwrap()
+            })
+            .collect()
+    }
+
+    fn combined(f: &Fixture) -> [u8; OUR_SIGNATURE_SIZE] {
+        our_ms::combine(&f.sig, &f.sk[0], M, f.ell, &f.ctx).unwrap().to_bytes()
+    }
+
+    #[test]
+    fn uneven_ranges_match_combine() {
+        let f = fixture(7);
+        let merged = merge_ranges(&ranges(&f, &[(1, 1), (2, 4), (6, 2)])).unwrap();
+
+        assert_eq!((merged.first(), merged.len()), (1, 7));
+        assert_eq!(finish(&merged, &f.sk[0]).unwrap().to_bytes(), combined(&f));
+    }
+
+    #[test]
+    fn out_of_order_and_nested_merges_match_combine() {
+        let f = fixture(7);
+        let parts = ranges(&f, &[(5, 3), (1, 2), (3, 2)]);
+        assert_eq!(finish(&merge_ranges(&parts).unwrap(), &f.sk[0]).unwrap().to_bytes(), combined(&f));
+
+        let tail = merge_ranges(&[parts[2].clone(), parts[0].clone()]).unwrap();
+        assert_eq!((tail.first(), tail.len()), (3, 5));
+        let all = merge_ranges(&[tail, parts[1].clone()]).unwrap();
+        assert_eq!(finish(&all, &f.sk[0]).unwrap().to_bytes(), combined(&f));
+    }
+
+    #[test]
+    fn merge_rejects_gap_and_overlap() {
+        let f = fixture(6);
+
+        assert!(matches!(merge_ranges(&ranges(&f, &[(1, 2), (4, 3)])), Err(Error::InvalidSignerIndex)));
+        assert!(matches!(merge_ranges(&ranges(&f, &[(1, 3), (3, 4)])), Err(Error::InvalidSignerIndex)));
+        assert!(matches!(merge_ranges(&[]), Err(Error::EmptySignerSet)));
+    }
+
+    #[test]
+    fn merge_rejects_different_ell_or_s2() {
+        let f = fixture(4);
+        let head = ranges(&f, &[(1, 2)]).remove(0);
+
+        let other_ell = combine_range(&f.sig[2..], 3, &f.sig[0], M, ChallengeLength::new(64).unwrap(), &f.ctx).unwrap();
+        assert!(matches!(merge_ranges(&[head.clone(), other_ell]), Err(Error::InvalidEll)));
+
+        let other_s2 = combine_range(&f.sig[2..], 3, &f.sig[1], M, f.ell, &f.ctx).unwrap();
+        assert!(matches!(merge_ranges(&[head, other_s2]), Err(Error::SignerSetMismatch)));
+    }
+
+    #[test]
+    fn first_range_must_start_with_s2() {
+        let f = fixture(3);
+
+        assert!(matches!(combine_range(&f.sig, 1, &f.sig[1], M, f.ell, &f.ctx), Err(Error::SignerSetMismatch)));
+        assert!(combine_range(&f.sig[1..], 2, &f.sig[0], M, f.ell, &f.ctx).is_ok());
+    }
+
+    #[test]
+    fn finish_needs_the_first_signer() {
+        let f = fixture(3);
+        let tail = ranges(&f, &[(2, 2)]).remove(0);
+
+        assert!(matches!(finish(&tail, &f.sk[0]), Err(Error::InvalidSignerIndex)));
+    }
+}
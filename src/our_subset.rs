@@ -0,0 +1,259 @@
+//! OUR-MS signed by any subset of a registered committee.
+//!
+//! The committee is a registered key list pk_1..pk_n. The participants P sign as in OUR-MS,
+//! except that each keeps its registered index i as weight and the first participant f
+//! produces S2:
+//!
+//! S1 = Σ_{i∈P} (sk_f + c + i)·σ_i, K1 = Σ_{i∈P} pk_i, K2 = Σ_{i∈P} i·pk_i + pk_f,
+//!
+//! so the usual `our_ms::verify` applies to the subset's (K1, K2). Since the indices are
+//! no longer consecutive, the weighted sums use a multi-scalar multiplication. The
+//! multisignature carries a [`Participation`] bitmap telling the verifier who signed.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::ecp::ECP;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::pair;
+use mcore::bls12381::rom;
+
+use crate::error::{Error, Result};
+use crate::msm::{g1_msm, g2_msm};
+use crate::our_ms::{self, ChallengeContext, ChallengeLength, OurAggregateKey, OurMultiSignature, OUR_SIGNATURE_SIZE};
+use crate::types::{PartialSignature, PublicKey, SecretKey};
+
+/// Which of the n registered signers took part, as a bitmap over indices 1..=n: index i is
+/// bit (i − 1) % 8 of byte (i − 1) / 8.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Participation {
+    registered: usize,
+    bitmap: Vec<u8>,
+}
+
+/// An OUR-MS multisignature of a subset of the committee.
+#[derive(Clone)]
+pub struct SubsetMultiSignature {
+    sig: OurMultiSignature,
+    participation: Participation,
+}
+
+impl Participation {
+    /// The participants among `registered` signers, by their 1-based registered index.
+    pub fn from_indices(registered: usize, indices: &[usize]) -> Result<Participation> {
+        if indices.is_empty() {
+            return Err(Error::EmptySignerSet);
+        }
+
+        let mut bitmap = vec![0u8; registered.div_ceil(8)];
+        for &i in indices {
+            if i == 0 || i > registered || bitmap[(i - 1) / 8] & (1 << ((i - 1) % 8)) != 0 {
+                return Err(Error::InvalidSignerIndex);
+            }
+            bitmap[(i - 1) / 8] |= 1 << ((i - 1) % 8);
+        }
+
+        Ok(Participation { registered, bitmap })
+    }
+
+    /// Reads a bitmap over `registered` signers. Bits past index n must be clear.
+    pub fn from_bitmap(registered: usize, bitmap: &[u8]) -> Result<Participation> {
+        if bitmap.len() != registered.div_ceil(8) {
+            return Err(Error::InvalidSignerIndex);
+        }
+        if !registered.is_multiple_of(8) && bitmap[bitmap.len() - 1] >> (registered % 8) != 0 {
+            return Err(Error::InvalidSignerIndex);
+        }
+        if bitmap.iter().all(|&b| b == 0) {
+            return Err(Error::EmptySignerSet);
+        }
+
+        Ok(Participation {
+            registered,
+            bitmap: bitmap.to_vec(),
+        })
+    }
+
+    /// The participants' registered indices, in ascending order.
+    pub fn indices(&self) -> Vec<usize> {
+        (1..=self.registered)
+            .filter(|i| self.bitmap[(i - 1) / 8] & (1 << ((i - 1) % 8)) != 0)
+            .collect()
+    }
+
+    /// The committee size n.
+    pub fn registered(&self) -> usize {
+        self.registered
+    }
+
+    pub fn bitmap(&self) -> &[u8] {
+        &self.bitmap
+    }
+}
+
+impl SubsetMultiSignature {
+    /// Decodes ell || S1 || S2 || n as 4 bytes big-endian || bitmap.
+    pub fn from_bytes(b: &[u8]) -> Result<SubsetMultiSignature> {
+        if b.len() < OUR_SIGNATURE_SIZE + 4 {
+            return Err(Error::InvalidPointEncoding);
+        }
+
+        let mut n: [u8; 4] = [0; 4];
+        n.copy_from_slice(&b[OUR_SIGNATURE_SIZE..OUR_SIGNATURE_SIZE + 4]);
+
+        Ok(SubsetMultiSignature {
+            sig: OurMultiSignature::from_bytes(&b[..OUR_SIGNATURE_SIZE])?,
+            participation: Participation::from_bitmap(u32::from_be_bytes(n) as usize, &b[OUR_SIGNATURE_SIZE + 4..])?,
+        })
+    }
+
+    /// Encodes the signature as ell || S1 || S2 || n as 4 bytes big-endian || bitmap.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out: Vec<u8> = Vec::with_capacity(OUR_SIGNATURE_SIZE + 4 + self.participation.bitmap.len());
+        out.extend(self.sig.to_bytes());
+        out.extend((self.participation.registered as u32).to_be_bytes());
+        out.extend(&self.participation.bitmap);
+
+        out
+    }
+
+    pub fn signature(&self) -> &OurMultiSignature {
+        &self.sig
+    }
+
+    pub fn participation(&self) -> &Participation {
+        &self.participation
+    }
+}
+
+/// Combines the partial signatures of the participants on `m`, given in ascending index
+/// order, using the secret key `sk` of the first participant.
+pub fn combine(
+    participation: &Participation,
+    sig: &[PartialSignature],
+    sk: &SecretKey,
+    m: &[u8],
+    ell: ChallengeLength,
+    ctx: &ChallengeContext,
+) -> Result<SubsetMultiSignature> {
+    let indices = participation.indices();
+    if sig.len() != indices.len() {
+        return Err(Error::SignerSetMismatch);
+    }
+
+    let order = BIG::new_ints(&rom::CURVE_ORDER);
+    let c = ctx.challenge(ell, &sig[0].0, m);
+
+    //(sk_f + c)·Σ σ_i + Σ i·σ_i
+    let points: Vec<ECP> = sig.iter().map(|s| s.0.clone()).collect();
+    let mut sum = ECP::new();
+    for p in &points {
+        sum.add(p);
+    }
+
+    let mut s1 = pair::g1mul(&sum, &BIG::modadd(&sk.0, &c, &order));
+    s1.add(&g1_msm(&points, &index_scalars(&indices)));
+
+    Ok(SubsetMultiSignature {
+        sig: OurMultiSignature {
+            s1,
+            s2: sig[0].0.clone(),
+            ell,
+        },
+        participation: participation.clone(),
+    })
+}
+
+/// Aggregates the participants' keys out of the `registered` committee into (K1, K2).
+pub fn aggregate_public_keys(registered: &[PublicKey], participation: &Participation) -> Result<OurAggregateKey> {
+    if registered.len() != participation.registered {
+        return Err(Error::SignerSetMismatch);
+    }
+
+    let indices = participation.indices();
+    let points: Vec<ECP2> = indices.iter().map(|&i| registered[i - 1].0.clone()).collect();
+
+    let mut k1 = ECP2::new();
+    for p in &points {
+        k1.add(p);
+    }
+
+    let mut k2 = g2_msm(&points, &index_scalars(&indices));
+    k2.add(&points[0]);
+
+    Ok(OurAggregateKey { k1, k2 })
+}
+
+/// Verifies `sig` on `m` against the `registered` committee and the participation it
+/// carries, under the challenge length `ell`.
+pub fn verify(
+    sig: &SubsetMultiSignature,
+    m: &[u8],
+    registered: &[PublicKey],
+    ell: ChallengeLength,
+    ctx: &ChallengeContext,
+) -> Result<()> {
+    our_ms::verify(&sig.sig, m, &aggregate_public_keys(registered, &sig.participation)?, ell, ctx)
+}
+
+fn index_scalars(indices: &[usize]) -> Vec<BIG> {
+    indices.iter().map(|&i| BIG::new_int(i as isize)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_rng;
+
+    const M: &[u8] = b"subset";
+
+    //a committee of 10 signed by 2, 5, 6 and 9
+    fn signed() -> (Vec<PublicKey>, SubsetMultiSignature, ChallengeLength, ChallengeContext) {
+        let mut rng = test_rng(18);
+        let (sk, pk): (Vec<SecretKey>, Vec<PublicKey>) = (0..10).map(|_| our_ms::keygen(&mut rng).unwrap()).unzip();
+        let participation = Participation::from_indices(10, &[9, 2, 6, 5]).unwrap();
+        let indices = participation.indices();
+
+        let sig: Vec<PartialSignature> = indices.iter().map(|&i| our_ms::sign(&sk[i - 1], M).unwrap()).collect();
+        let ell = ChallengeLength::new(128).unwrap();
+        let ctx = ChallengeContext::for_key(&aggregate_public_keys(&pk, &participation).unwrap());
+        let sigma = combine(&participation, &sig, &sk[indices[0] - 1], M, ell, &ctx).unwrap();
+
+        (pk, sigma, ell, ctx)
+    }
+
+    #[test]
+    fn verifies_and_round_trips() {
+        let (pk, sigma, ell, ctx) = signed();
+        assert_eq!(sigma.participation().indices(), vec![2, 5, 6, 9]);
+        assert_eq!(verify(&sigma, M, &pk, ell, &ctx), Ok(()));
+
+        let decoded = SubsetMultiSignature::from_bytes(&sigma.to_bytes()).unwrap();
+        assert_eq!(decoded.to_bytes(), sigma.to_bytes());
+        assert_eq!(decoded.participation(), sigma.participation());
+        assert_eq!(verify(&decoded, M, &pk, ell, &ctx), Ok(()));
+    }
+
+    #[test]
+    fn rejects_flipped_participation_bit() {
+        let (pk, sigma, ell, ctx) = signed();
+
+        for index in [1, 2, 10] {
+            let mut b = sigma.to_bytes();
+            b[OUR_SIGNATURE_SIZE + 4 + (index - 1) / 8] ^= 1 << ((index - 1) % 8);
+
+            let flipped = SubsetMultiSignature::from_bytes(&b).unwrap();
+            assert_eq!(verify(&flipped, M, &pk, ell, &ctx), Err(Error::VerificationFailed));
+        }
+    }
+
+    #[test]
+    fn from_bitmap_rejects_malformed_bitmaps() {
+        assert_eq!(Participation::from_bitmap(10, &[0x01]), Err(Error::InvalidSignerIndex));
+        assert_eq!(Participation::from_bitmap(10, &[0x01, 0x00, 0x00]), Err(Error::InvalidSignerIndex));
+        //index 11 of 10
+        assert_eq!(Participation::from_bitmap(10, &[0x01, 0x04]), Err(Error::InvalidSignerIndex));
+        assert_eq!(Participation::from_bitmap(10, &[0x00, 0x00]), Err(Error::EmptySignerSet));
+
+        assert_eq!(Participation::from_bitmap(10, &[0x01, 0x02]).unwrap().indices(), vec![1, 10]);
+        assert_eq!(Participation::from_bitmap(8, &[0x80]).unwrap().indices(), vec![8]);
+    }
+}
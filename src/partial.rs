@@ -0,0 +1,189 @@
+//! Batched checks of partial signatures against their signers' keys, before combining.
+//!
+//! Partial signature σ_i is valid when e(σ_i, g2) = e(H(m), w_i·pk_i), where w_i is 1 for
+//! OUR-MS and noPoP and the BDN coefficient for BDN-MS. With scalars r_i derived from a
+//! hash of all inputs, e(Σ r_i·σ_i, g2) = e(H(m), Σ r_i·w_i·pk_i) checks all n at once
+//! with two pairings and two multi-scalar multiplications. If that fails, the set is
+//! bisected to find the invalid ones.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::ecp::ECP;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::pair;
+use mcore::bls12381::rom;
+use mcore::hmac;
+use mcore::rand::RAND;
+
+use crate::error::{Error, Result};
+use crate::msm::{g1_msm, g2_msm};
+use crate::types::{g1_to_bytes, g2_to_bytes, PartialSignature};
+
+/// Domain-separation tag for deriving the batch scalars.
+const PARTIAL_CHECK_DST: &[u8] = b"BLS_INCSIGN_PARTIAL_CHECK_BLS12381G1_XMD:SHA-256_";
+/// Bit length of the batch scalars.
+const PARTIAL_SCALAR_BITS: usize = 64;
+
+/// Checks every partial signature in `sig` on H(m) = `hash_m` against the key `keys[i]`
+/// scaled by `weights[i]`. Fails with the 1-based indices of the invalid ones.
+pub(crate) fn check_partials(sig: &[PartialSignature], keys: &[ECP2], weights: &[BIG], hash_m: &ECP) -> Result<()> {
+    if sig.len() != keys.len() || sig.len() != weights.len() {
+        return Err(Error::SignerSetMismatch);
+    }
+    if sig.is_empty() {
+        return Err(Error::EmptySignerSet);
+    }
+
+    let order = BIG::new_ints(&rom::CURVE_ORDER);
+    let mut rng = scalar_rng(sig, keys);
+
+    let points: Vec<ECP> = sig.iter().map(|s| s.0.clone()).collect();
+    let r: Vec<BIG> = (0..sig.len()).map(|_| BIG::randtrunc(&order, PARTIAL_SCALAR_BITS, &mut rng)).collect();
+    let rw: Vec<BIG> = r.iter().zip(weights).map(|(r_, w)| BIG::modmul(r_, w, &order)).collect();
+
+    let batch = Batch {
+        points: &points,
+        keys,
+        r: &r,
+        rw: &rw,
+        hash_m,
+    };
+
+    let mut invalid = Vec::new();
+    batch.bisect(0, sig.len(), &mut invalid);
+
+    if invalid.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::InvalidPartialSignatures(invalid))
+    }
+}
+
+struct Batch<'a> {
+    points: &'a [ECP],
+    keys: &'a [ECP2],
+    r: &'a [BIG],
+    //r_i·w_i
+    rw: &'a [BIG],
+    hash_m: &'a ECP,
+}
+
+impl Batch<'_> {
+    fn bisect(&self, from: usize, to: usize, invalid: &mut Vec<usize>) {
+        if from == to || self.check(from, to) {
+            return;
+        }
+        if to - from == 1 {
+            invalid.push(from + 1);
+            return;
+        }
+
+        let mid = from + (to - from) / 2;
+        self.bisect(from, mid, invalid);
+        self.bisect(mid, to, invalid);
+    }
+
+    //e(Σ r_i·σ_i, g2) · e(-H(m), Σ r_i·w_i·pk_i) == 1 over from..to
+    fn check(&self, from: usize, to: usize) -> bool {
+        let lhs = g1_msm(&self.points[from..to], &self.r[from..to]);
+        let rhs = g2_msm(&self.keys[from..to], &self.rw[from..to]);
+
+        let mut h = self.hash_m.clone();
+        h.neg();
+
+        let mut r = pair::initmp();
+        pair::another(&mut r, &ECP2::generator(), &lhs);
+        pair::another(&mut r, &rhs, &h);
+
+        let mut v = pair::miller(&mut r);
+        v = pair::fexp(&v);
+
+        v.isunity()
+    }
+}
+
+//seeded from a hash of every partial signature and key, so the scalars are fixed only
+//after all inputs are
+fn scalar_rng(sig: &[PartialSignature], keys: &[ECP2]) -> RAND {
+    let mut input: Vec<u8> = Vec::new();
+    for s in sig {
+        input.extend(g1_to_bytes(&s.0));
+    }
+    for k in keys {
+        input.extend(g2_to_bytes(k));
+    }
+
+    let mut seed: [u8; 32] = [0; 32];
+    hmac::xmd_expand(hmac::MC_SHA2, 32, &mut seed, 32, PARTIAL_CHECK_DST, &input);
+
+    let mut rng = RAND::new();
+    rng.clean();
+    rng.seed(32, &seed);
+
+    rng
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::our_ms::{self, ChallengeContext, ChallengeLength};
+    use crate::types::{PartialSignature, PublicKey, SecretKey};
+    use crate::utils::test_rng;
+    use crate::{bdn_ms, nopop_ms};
+
+    const M: &[u8] = b"partials";
+
+    fn keys(n: usize) -> (Vec<SecretKey>, Vec<PublicKey>) {
+        let mut rng = test_rng(23);
+        (0..n).map(|_| our_ms::keygen(&mut rng).unwrap()).unzip()
+    }
+
+    fn our_combine_verified(sig: &[PartialSignature], sk: &[SecretKey], pk: &[PublicKey]) -> Result<(), Error> {
+        let ctx = ChallengeContext::for_key(&our_ms::aggregate_public_keys(pk)?);
+        our_ms::combine_verified(sig, pk, &sk[0], M, ChallengeLength::new(128)?, &ctx).map(|_| ())
+    }
+
+    #[test]
+    fn names_swapped_partials() {
+        let (sk, pk) = keys(5);
+        let mut sig: Vec<PartialSignature> = sk.iter().map(|sk_| our_ms::sign(sk_, M).unwrap()).collect();
+        assert_eq!(our_combine_verified(&sig, &sk, &pk), Ok(()));
+
+        sig.swap(1, 3);
+        assert_eq!(our_combine_verified(&sig, &sk, &pk), Err(Error::InvalidPartialSignatures(vec![2, 4])));
+        assert_eq!(nopop_ms::combine_verified(&sig, &pk, M).err(), Some(Error::InvalidPartialSignatures(vec![2, 4])));
+    }
+
+    #[test]
+    fn names_foreign_partials() {
+        let (sk, pk) = keys(6);
+        let mut sig: Vec<PartialSignature> = sk.iter().map(|sk_| nopop_ms::sign(sk_, M).unwrap()).collect();
+        sig[0] = nopop_ms::sign(&sk[0], b"another message").unwrap();
+        sig[5] = nopop_ms::sign(&keys(7).0[6], M).unwrap();
+
+        assert_eq!(nopop_ms::combine_verified(&sig, &pk, M).err(), Some(Error::InvalidPartialSignatures(vec![1, 6])));
+        assert_eq!(our_combine_verified(&sig, &sk, &pk), Err(Error::InvalidPartialSignatures(vec![1, 6])));
+    }
+
+    #[test]
+    fn applies_bdn_coefficients() {
+        let (sk, pk) = keys(4);
+        let mut sig: Vec<PartialSignature> = sk.iter().zip(&pk).map(|(sk_, pk_)| bdn_ms::sign(sk_, pk_, &pk, M).unwrap()).collect();
+
+        let sigma = bdn_ms::combine_verified(&sig, &pk, M).unwrap();
+        assert_eq!(bdn_ms::verify(&sigma, M, &bdn_ms::aggregate_public_keys(&pk).unwrap()), Ok(()));
+
+        //a valid plain BLS signature lacks the coefficient
+        sig[2] = nopop_ms::sign(&sk[2], M).unwrap();
+        assert_eq!(bdn_ms::combine_verified(&sig, &pk, M).err(), Some(Error::InvalidPartialSignatures(vec![3])));
+    }
+
+    #[test]
+    fn rejects_length_mismatch() {
+        let (sk, pk) = keys(3);
+        let sig: Vec<PartialSignature> = sk.iter().map(|sk_| nopop_ms::sign(sk_, M).unwrap()).collect();
+
+        assert_eq!(nopop_ms::combine_verified(&sig[..2], &pk, M).err(), Some(Error::SignerSetMismatch));
+        assert_eq!(our_combine_verified(&sig, &sk, &pk[..2]), Err(Error::SignerSetMismatch));
+        assert_eq!(bdn_ms::combine_verified(&sig, &pk[..1], M).err(), Some(Error::SignerSetMismatch));
+    }
+}
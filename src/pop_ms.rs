@@ -0,0 +1,161 @@
+//! BLS multi-signatures with proof of possession (Boldyreva 2003, IETF BLS draft).
+//!
+//! Keys are summed exactly as in `nopop_ms`; rogue keys are ruled out because every signer
+//! first proves knowledge of its secret key with a [`ProofOfPossession`], which the
+//! verifier checks once per key with [`pop_verify`]. Afterwards [`fast_aggregate_verify`]
+//! needs only one key sum and two pairings.
+//!
+//! As in IETF PopProve, the proof is sk·H_pop(pk), where H_pop hashes to G1 under its own
+//! tag [`POP_DST`] rather than the message tag of `bls_hash_to_point`. No signature on any
+//! message therefore doubles as a proof of possession.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::dbig::DBIG;
+use mcore::bls12381::ecp;
+use mcore::bls12381::ecp::ECP;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::fp::FP;
+use mcore::bls12381::pair;
+use mcore::bls12381::rom;
+use mcore::hmac;
+use mcore::rand::RAND;
+
+use crate::error::{Error, Result};
+use crate::nopop_ms;
+use crate::types::{g1_from_bytes, g1_to_bytes, AggregatePublicKey, MultiSignature, PartialSignature, PublicKey, SecretKey};
+use crate::utils::bls_setup;
+use crate::G1S;
+
+/// Domain-separation tag for hashing a public key to G1 in a proof of possession.
+pub const POP_DST: &[u8] = b"BLS_INCSIGN_POP_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+
+/// A signer's proof that it knows the secret key of its public key.
+#[derive(Clone)]
+pub struct ProofOfPossession(pub(crate) ECP);
+
+impl ProofOfPossession {
+    /// Decodes a compressed G1 point, checking that it lies in the prime-order subgroup.
+    pub fn from_bytes(b: &[u8]) -> Result<ProofOfPossession> {
+        Ok(ProofOfPossession(g1_from_bytes(b)?))
+    }
+
+    /// Encodes the proof as a compressed G1 point.
+    pub fn to_bytes(&self) -> [u8; G1S] {
+        g1_to_bytes(&self.0)
+    }
+}
+
+/// Generates a signer key pair.
+pub fn keygen(rng: &mut RAND) -> Result<(SecretKey, PublicKey)> {
+    bls_setup(rng)
+}
+
+/// Proves possession of `sk` for its public key `pk`.
+pub fn pop_prove(sk: &SecretKey, pk: &PublicKey) -> ProofOfPossession {
+    ProofOfPossession(pair::g1mul(&hash_to_g1(POP_DST, &pk.to_bytes()), &sk.0))
+}
+
+/// Checks e(proof, g2) == e(H_pop(pk), pk). Needed once per key, before it is aggregated.
+pub fn pop_verify(pk: &PublicKey, proof: &ProofOfPossession) -> Result<()> {
+    let mut p = proof.0.clone();
+    p.neg();
+
+    let mut r = pair::initmp();
+    pair::another(&mut r, &ECP2::generator(), &p);
+    pair::another(&mut r, &pk.0, &hash_to_g1(POP_DST, &pk.to_bytes()));
+
+    let mut v = pair::miller(&mut r);
+    v = pair::fexp(&v);
+
+    if v.isunity() {
+        Ok(())
+    } else {
+        Err(Error::VerificationFailed)
+    }
+}
+
+/// Produces the partial signature of `sk` on `m`.
+pub fn sign(sk: &SecretKey, m: &[u8]) -> Result<PartialSignature> {
+    nopop_ms::sign(sk, m)
+}
+
+/// Combines partial signatures by summing them.
+pub fn combine(sig: &[PartialSignature]) -> Result<MultiSignature> {
+    nopop_ms::combine(sig)
+}
+
+/// Aggregates public keys by summing them. Every key must have passed [`pop_verify`].
+pub fn aggregate_public_keys(pkvec: &[PublicKey]) -> Result<AggregatePublicKey> {
+    nopop_ms::aggregate_public_keys(pkvec)
+}
+
+/// Checks e(sigma, g2) == e(H(m), apk).
+pub fn verify(sig: &MultiSignature, m: &[u8], apk: &AggregatePublicKey) -> Result<()> {
+    nopop_ms::verify(sig, m, apk)
+}
+
+/// Verifies `sig` on `m` against the signer set `pkvec`, as IETF FastAggregateVerify.
+/// Every key must have passed [`pop_verify`].
+pub fn fast_aggregate_verify(pkvec: &[PublicKey], m: &[u8], sig: &MultiSignature) -> Result<()> {
+    verify(sig, m, &aggregate_public_keys(pkvec)?)
+}
+
+//hash_to_curve of RFC 9380 under `dst`: two field elements from expand_message_xmd, each
+//mapped to the curve, summed and cleared of the cofactor, as bls_hash_to_point does under
+//its fixed message tag
+fn hash_to_g1(dst: &[u8], m: &[u8]) -> ECP {
+    let q = BIG::new_ints(&rom::MODULUS);
+    let el = (q.nbits() + 8 * ecp::AESKEY).div_ceil(8);
+
+    let mut okm: [u8; 256] = [0; 256];
+    hmac::xmd_expand(hmac::MC_SHA2, ecp::HASH_TYPE, &mut okm, 2 * el, dst, m);
+
+    let u: Vec<FP> = okm[..2 * el]
+        .chunks(el)
+        .map(|fd| FP::new_big(&DBIG::frombytes(fd).dmod(&q)))
+        .collect();
+
+    let mut p = ECP::map2point(&u[0]);
+    p.add(&ECP::map2point(&u[1]));
+    p.cfp();
+    p.affine();
+
+    p
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_rng;
+    use mcore::bls12381::bls::bls_hash_to_point;
+
+    //the tag bls_hash_to_point uses
+    const MESSAGE_DST: &[u8] = b"BLS_SIG_ZZZG1_XMD:SHA256-SVDW-RO-_NUL_";
+
+    #[test]
+    fn hash_matches_miracl_under_the_message_tag() {
+        let m = b"proof of possession";
+        assert!(hash_to_g1(MESSAGE_DST, m).equals(&bls_hash_to_point(m)));
+        assert!(!hash_to_g1(POP_DST, m).equals(&bls_hash_to_point(m)));
+    }
+
+    #[test]
+    fn proof_verifies_for_its_own_key_only() {
+        let mut rng = test_rng(13);
+        let (sk, pk) = keygen(&mut rng).unwrap();
+        let (_, other) = keygen(&mut rng).unwrap();
+
+        let proof = pop_prove(&sk, &pk);
+        assert_eq!(pop_verify(&pk, &proof), Ok(()));
+        assert_eq!(pop_verify(&other, &proof), Err(Error::VerificationFailed));
+    }
+
+    //signing the key bytes as an ordinary message does not yield a proof
+    #[test]
+    fn signature_on_key_is_not_a_proof() {
+        let (sk, pk) = keygen(&mut test_rng(13)).unwrap();
+        let sig = sign(&sk, &pk.to_bytes()).unwrap();
+
+        assert_eq!(pop_verify(&pk, &ProofOfPossession(sig.0)), Err(Error::VerificationFailed));
+    }
+}
@@ -0,0 +1,163 @@
+//! Signer sets with an agreed order, for OUR-MS.
+//!
+//! OUR-MS weights signer i by its index, so signers, combiner and verifier must use the
+//! same order of keys. A [`SignerSet`] fixes that order once, either canonically by the
+//! compressed key bytes or from an explicit index map, and rejects sets containing a key
+//! twice. Its [`keys`](SignerSet::keys) are then what `our_ms::aggregate_public_keys`,
+//! `ChallengeContext::bind_signer_set` and `our_ms::combine` take, and
+//! [`index_of`](SignerSet::index_of) gives each signer its index for incremental signing.
+
+use crate::error::{Error, Result};
+use crate::types::PublicKey;
+use crate::G2S;
+
+/// Public keys in signer order, index 1 first.
+#[derive(Clone)]
+pub struct SignerSet {
+    keys: Vec<PublicKey>,
+    //compressed keys with their 1-based index, sorted by the bytes
+    lookup: Vec<([u8; G2S], usize)>,
+}
+
+impl SignerSet {
+    /// Orders the keys by their compressed encoding, independently of the input order.
+    pub fn canonical(pkvec: &[PublicKey]) -> Result<SignerSet> {
+        let mut keys = pkvec.to_vec();
+        keys.sort_by_cached_key(|pk| pk.to_bytes());
+
+        SignerSet::new(keys)
+    }
+
+    /// Places each key at its given index. The indices must be exactly 1..=n.
+    pub fn from_indices(map: &[(usize, PublicKey)]) -> Result<SignerSet> {
+        let mut slots: Vec<Option<PublicKey>> = vec![None; map.len()];
+
+        for (i, pk) in map {
+            match slots.get_mut(i.wrapping_sub(1)) {
+                Some(slot @ None) => *slot = Some(pk.clone()),
+                _ => return Err(Error::InvalidSignerIndex),
+            }
+        }
+
+        SignerSet::new(slots.into_iter().flatten().collect())
+    }
+
+    fn new(keys: Vec<PublicKey>) -> Result<SignerSet> {
+        if keys.is_empty() {
+            return Err(Error::EmptySignerSet);
+        }
+
+        let mut lookup: Vec<([u8; G2S], usize)> = keys.iter().enumerate().map(|(i, pk)| (pk.to_bytes(), i + 1)).collect();
+        lookup.sort_unstable();
+
+        if lookup.windows(2).any(|w| w[0].0 == w[1].0) {
+            return Err(Error::DuplicateKey);
+        }
+
+        Ok(SignerSet { keys, lookup })
+    }
+
+    /// The keys in signer order.
+    pub fn keys(&self) -> &[PublicKey] {
+        &self.keys
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Always false; a signer set has at least one key.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The 1-based index of `pk`, if it belongs to the set.
+    pub fn index_of(&self, pk: &PublicKey) -> Option<usize> {
+        let bytes = pk.to_bytes();
+
+        self.lookup
+            .binary_search_by(|(b, _)| b.cmp(&bytes))
+            .ok()
+            .map(|at| self.lookup[at].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::our_ms::{self, aggregate_public_keys, ChallengeContext, ChallengeLength};
+    use crate::types::{PartialSignature, SecretKey};
+    use crate::utils::test_rng;
+
+    const M: &[u8] = b"signer set";
+
+    fn keys(n: usize) -> (Vec<SecretKey>, Vec<PublicKey>) {
+        let mut rng = test_rng(17);
+        (0..n).map(|_| our_ms::keygen(&mut rng).unwrap()).unzip()
+    }
+
+    fn bytes(pkvec: &[PublicKey]) -> Vec<[u8; G2S]> {
+        pkvec.iter().map(|pk| pk.to_bytes()).collect()
+    }
+
+    #[test]
+    fn canonical_ignores_input_order() {
+        let (_, pk) = keys(5);
+        let mut shuffled = pk.clone();
+        shuffled.reverse();
+        shuffled.swap(1, 3);
+
+        let set = SignerSet::canonical(&pk).unwrap();
+        assert_eq!(bytes(set.keys()), bytes(SignerSet::canonical(&shuffled).unwrap().keys()));
+        assert!(set.keys().windows(2).all(|w| w[0].to_bytes() < w[1].to_bytes()));
+        for (i, pk_) in set.keys().iter().enumerate() {
+            assert_eq!(set.index_of(pk_), Some(i + 1));
+        }
+    }
+
+    #[test]
+    fn rejects_repeated_key() {
+        let (_, pk) = keys(3);
+        let repeated = [pk[0].clone(), pk[1].clone(), pk[0].clone()];
+
+        assert!(matches!(SignerSet::canonical(&repeated), Err(Error::DuplicateKey)));
+        assert!(matches!(SignerSet::from_indices(&[(1, pk[2].clone()), (2, pk[2].clone())]), Err(Error::DuplicateKey)));
+        assert!(matches!(SignerSet::canonical(&[]), Err(Error::EmptySignerSet)));
+    }
+
+    #[test]
+    fn from_indices_requires_one_to_n() {
+        let (_, pk) = keys(3);
+        let map = |indices: [usize; 3]| -> Vec<(usize, PublicKey)> { indices.into_iter().zip(pk.iter().cloned()).collect() };
+
+        assert!(matches!(SignerSet::from_indices(&map([0, 1, 2])), Err(Error::InvalidSignerIndex)));
+        assert!(matches!(SignerSet::from_indices(&map([1, 2, 4])), Err(Error::InvalidSignerIndex)));
+        assert!(matches!(SignerSet::from_indices(&map([1, 3, 1])), Err(Error::InvalidSignerIndex)));
+
+        let set = SignerSet::from_indices(&map([3, 1, 2])).unwrap();
+        assert_eq!(bytes(set.keys()), bytes(&[pk[1].clone(), pk[2].clone(), pk[0].clone()]));
+    }
+
+    #[test]
+    fn combine_in_set_places_signatures_by_key() {
+        let (sk, pk) = keys(5);
+        let set = SignerSet::canonical(&pk).unwrap();
+        let ell = ChallengeLength::new(128).unwrap();
+        let ctx = ChallengeContext::for_key(&aggregate_public_keys(set.keys()).unwrap());
+
+        //partial signatures in input order, the combiner being the set's first signer
+        let sig: Vec<(PublicKey, PartialSignature)> = sk.iter().zip(&pk).map(|(sk_, pk_)| (pk_.clone(), our_ms::sign(sk_, M).unwrap())).collect();
+        let first = &sk[pk.iter().position(|pk_| set.index_of(pk_) == Some(1)).unwrap()];
+
+        let ordered: Vec<PartialSignature> = set
+            .keys()
+            .iter()
+            .map(|pk_| sig.iter().find(|(p, _)| p.to_bytes() == pk_.to_bytes()).unwrap().1.clone())
+            .collect();
+        let expected = our_ms::combine(&ordered, first, M, ell, &ctx).unwrap();
+
+        let mut shuffled = sig.clone();
+        shuffled.rotate_left(2);
+        assert_eq!(our_ms::combine_in_set(&set, &shuffled, first, M, ell, &ctx).unwrap().to_bytes(), expected.to_bytes());
+    }
+}
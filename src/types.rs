@@ -0,0 +1,339 @@
+//! Decoded key and signature types shared by all schemes.
+//!
+//! Values are kept as decoded MIRACL group elements and scalars; bytes only appear at the
+//! `to_bytes`/`from_bytes` boundary.
+
+use mcore::bls12381::big::BIG;
+use mcore::bls12381::ecp::ECP;
+use mcore::bls12381::ecp2::ECP2;
+use mcore::bls12381::pair;
+use mcore::bls12381::rom;
+
+use crate::error::{Error, Result};
+use crate::{BGS, G1S, G2S};
+
+/// A signer's secret scalar.
+#[derive(Clone)]
+pub struct SecretKey(pub(crate) BIG);
+
+/// A signer's public key in G2.
+#[derive(Clone)]
+pub struct PublicKey(pub(crate) ECP2);
+
+/// A single signer's BLS signature in G1, before combining.
+#[derive(Clone)]
+pub struct PartialSignature(pub(crate) ECP);
+
+/// A combined BDN-MS or noPoP multisignature in G1.
+#[derive(Clone)]
+pub struct MultiSignature(pub(crate) ECP);
+
+/// A BDN-MS or noPoP aggregated public key in G2.
+#[derive(Clone)]
+pub struct AggregatePublicKey(pub(crate) ECP2);
+
+impl SecretKey {
+    pub fn from_bytes(b: &[u8]) -> Result<SecretKey> {
+        if b.len() != BGS {
+            return Err(Error::InvalidSecretKey);
+        }
+
+        let x = BIG::frombytes(b);
+        if x.iszilch() || BIG::comp(&x, &BIG::new_ints(&rom::CURVE_ORDER)) >= 0 {
+            return Err(Error::InvalidSecretKey);
+        }
+
+        Ok(SecretKey(x))
+    }
+
+    pub fn to_bytes(&self) -> [u8; BGS] {
+        let mut n: [u8; BGS] = [0; BGS];
+        self.0.tobytes(&mut n);
+
+        n
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(pair::g2mul(&ECP2::generator(), &self.0))
+    }
+}
+
+impl PublicKey {
+    pub fn from_bytes(b: &[u8]) -> Result<PublicKey> {
+        Ok(PublicKey(g2_from_bytes(b)?))
+    }
+
+    /// Decodes a key that was validated before, e.g. when it was registered, skipping the
+    /// subgroup check. Encoding and identity checks still apply.
+    pub fn from_bytes_unchecked(b: &[u8]) -> Result<PublicKey> {
+        Ok(PublicKey(g2_decode(b)?))
+    }
+
+    /// Checks that the key is not the identity and lies in the prime-order subgroup.
+    pub fn validate(&self) -> Result<()> {
+        g2_validate(&self.0)
+    }
+
+    pub fn to_bytes(&self) -> [u8; G2S] {
+        g2_to_bytes(&self.0)
+    }
+}
+
+impl PartialSignature {
+    pub fn from_bytes(b: &[u8]) -> Result<PartialSignature> {
+        Ok(PartialSignature(g1_from_bytes(b)?))
+    }
+
+    pub fn to_bytes(&self) -> [u8; G1S] {
+        g1_to_bytes(&self.0)
+    }
+}
+
+impl MultiSignature {
+    pub fn from_bytes(b: &[u8]) -> Result<MultiSignature> {
+        Ok(MultiSignature(g1_from_bytes(b)?))
+    }
+
+    pub fn to_bytes(&self) -> [u8; G1S] {
+        g1_to_bytes(&self.0)
+    }
+}
+
+impl AggregatePublicKey {
+    pub fn from_bytes(b: &[u8]) -> Result<AggregatePublicKey> {
+        Ok(AggregatePublicKey(g2_from_bytes(b)?))
+    }
+
+    pub fn to_bytes(&self) -> [u8; G2S] {
+        g2_to_bytes(&self.0)
+    }
+}
+
+/// Decodes a compressed G1 point, checking that it is on the curve, not the identity and in
+/// the subgroup.
+pub(crate) fn g1_from_bytes(b: &[u8]) -> Result<ECP> {
+    let p = g1_decode(b)?;
+    g1_validate(&p)?;
+
+    Ok(p)
+}
+
+/// Decodes a compressed G2 point, checking that it is on the curve, not the identity and in
+/// the subgroup.
+pub(crate) fn g2_from_bytes(b: &[u8]) -> Result<ECP2> {
+    let p = g2_decode(b)?;
+    g2_validate(&p)?;
+
+    Ok(p)
+}
+
+pub(crate) fn g1_validate(p: &ECP) -> Result<()> {
+    if p.is_infinity() {
+        return Err(Error::IdentityPoint);
+    }
+    if !pair::g1member(p) {
+        return Err(Error::PointNotInSubgroup);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn g2_validate(p: &ECP2) -> Result<()> {
+    if p.is_infinity() {
+        return Err(Error::IdentityPoint);
+    }
+    if !pair::g2member(p) {
+        return Err(Error::PointNotInSubgroup);
+    }
+
+    Ok(())
+}
+
+//on-curve and identity checks only
+fn g1_decode(b: &[u8]) -> Result<ECP> {
+    if b.len() != G1S || !is_compressed(b) {
+        return Err(Error::InvalidPointEncoding);
+    }
+    if is_identity_encoding(b) {
+        return Err(Error::IdentityPoint);
+    }
+
+    let p = ECP::frombytes(b);
+    if p.is_infinity() {
+        return Err(Error::InvalidPointEncoding);
+    }
+
+    Ok(p)
+}
+
+fn g2_decode(b: &[u8]) -> Result<ECP2> {
+    if b.len() != G2S || !is_compressed(b) {
+        return Err(Error::InvalidPointEncoding);
+    }
+    if is_identity_encoding(b) {
+        return Err(Error::IdentityPoint);
+    }
+
+    //MIRACL reduces each half of x mod p, which would give every point a second encoding
+    let modulus = BIG::new_ints(&rom::MODULUS);
+    if b[1..].chunks(BGS).any(|half| BIG::comp(&BIG::frombytes(half), &modulus) >= 0) {
+        return Err(Error::InvalidPointEncoding);
+    }
+
+    let p = ECP2::frombytes(b);
+    if p.is_infinity() {
+        return Err(Error::InvalidPointEncoding);
+    }
+
+    Ok(p)
+}
+
+//MIRACL reads an uncompressed body after 0x04, past the end of a compressed-size slice,
+//and G2 takes any other flag byte as a parity bit
+fn is_compressed(b: &[u8]) -> bool {
+    b[0] == 0x02 || b[0] == 0x03
+}
+
+//MIRACL encodes the identity as a flag byte and x = 0, which in G1 decodes to the order-3
+//point (0, ±2) rather than the identity; anything else that decodes to the identity is off
+//the curve
+fn is_identity_encoding(b: &[u8]) -> bool {
+    b[1..].iter().all(|&x| x == 0)
+}
+
+pub(crate) fn g1_to_bytes(p: &ECP) -> [u8; G1S] {
+    let mut point: [u8; G1S] = [0; G1S];
+    p.tobytes(&mut point, true);
+
+    point
+}
+
+pub(crate) fn g2_to_bytes(p: &ECP2) -> [u8; G2S] {
+    let mut point: [u8; G2S] = [0; G2S];
+    p.tobytes(&mut point, true);
+
+    point
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //compressed encodings with x = k; below are the smallest x off the curve and the
+    //smallest on it, whose points lie outside the prime-order subgroup
+    fn g1_with_x(k: u8) -> [u8; G1S] {
+        let mut b: [u8; G1S] = [0; G1S];
+        b[0] = 0x02;
+        b[G1S - 1] = k;
+        b
+    }
+
+    fn g2_with_x(k: u8) -> [u8; G2S] {
+        let mut b: [u8; G2S] = [0; G2S];
+        b[0] = 0x02;
+        b[G2S - 1] = k;
+        b
+    }
+
+    const G1_OFF_CURVE: u8 = 1;
+    const G1_NOT_IN_SUBGROUP: u8 = 4;
+    const G2_OFF_CURVE: u8 = 2;
+    const G2_NOT_IN_SUBGROUP: u8 = 1;
+
+    fn valid_pk() -> PublicKey {
+        let mut b: [u8; BGS] = [0; BGS];
+        b[BGS - 1] = 7;
+        SecretKey::from_bytes(&b).unwrap().public_key()
+    }
+
+    #[test]
+    fn round_trip() {
+        let pk = valid_pk();
+        assert_eq!(PublicKey::from_bytes(&pk.to_bytes()).unwrap().to_bytes(), pk.to_bytes());
+
+        let sig = PartialSignature(pair::g1mul(&ECP::generator(), &BIG::new_int(7)));
+        assert_eq!(PartialSignature::from_bytes(&sig.to_bytes()).unwrap().to_bytes(), sig.to_bytes());
+    }
+
+    #[test]
+    fn rejects_wrong_length() {
+        let g1 = g1_to_bytes(&ECP::generator());
+        let g2 = g2_to_bytes(&ECP2::generator());
+
+        assert_eq!(g1_from_bytes(&g1[..G1S - 1]).err(), Some(Error::InvalidPointEncoding));
+        assert_eq!(g1_from_bytes(&[&g1[..], &[0]].concat()).err(), Some(Error::InvalidPointEncoding));
+        assert_eq!(g2_from_bytes(&g2[..G2S - 1]).err(), Some(Error::InvalidPointEncoding));
+        assert_eq!(g2_from_bytes(&[]).err(), Some(Error::InvalidPointEncoding));
+    }
+
+    #[test]
+    fn rejects_off_curve() {
+        assert_eq!(g1_from_bytes(&g1_with_x(G1_OFF_CURVE)).err(), Some(Error::InvalidPointEncoding));
+        assert_eq!(g2_from_bytes(&g2_with_x(G2_OFF_CURVE)).err(), Some(Error::InvalidPointEncoding));
+    }
+
+    #[test]
+    fn rejects_other_prefixes() {
+        let g1 = PartialSignature(ECP::generator()).to_bytes();
+        let g2 = valid_pk().to_bytes();
+
+        for prefix in [0x00, 0x04, 0x07] {
+            let mut b = g1;
+            b[0] = prefix;
+            assert_eq!(PartialSignature::from_bytes(&b).err(), Some(Error::InvalidPointEncoding));
+
+            let mut b = g2;
+            b[0] = prefix;
+            assert_eq!(PublicKey::from_bytes(&b).err(), Some(Error::InvalidPointEncoding));
+            assert_eq!(PublicKey::from_bytes_unchecked(&b).err(), Some(Error::InvalidPointEncoding));
+        }
+    }
+
+    #[test]
+    fn rejects_coordinate_above_modulus() {
+        //x + p in place of x, for x in G1 and each half of x in G2
+        let plus_modulus = |b: &mut [u8]| {
+            let mut x = BIG::frombytes(b);
+            x.add(&BIG::new_ints(&rom::MODULUS));
+            x.norm();
+            x.tobytes(b);
+        };
+
+        let mut g1 = PartialSignature(ECP::generator()).to_bytes();
+        plus_modulus(&mut g1[1..]);
+        assert_eq!(PartialSignature::from_bytes(&g1).err(), Some(Error::InvalidPointEncoding));
+
+        for half in [1, 1 + BGS] {
+            let mut g2 = valid_pk().to_bytes();
+            plus_modulus(&mut g2[half..half + BGS]);
+            assert_eq!(PublicKey::from_bytes(&g2).err(), Some(Error::InvalidPointEncoding));
+        }
+    }
+
+    #[test]
+    fn rejects_identity() {
+        assert_eq!(g1_from_bytes(&g1_to_bytes(&ECP::new())).err(), Some(Error::IdentityPoint));
+        assert_eq!(g2_from_bytes(&g2_to_bytes(&ECP2::new())).err(), Some(Error::IdentityPoint));
+    }
+
+    #[test]
+    fn rejects_point_outside_subgroup() {
+        assert_eq!(g1_from_bytes(&g1_with_x(G1_NOT_IN_SUBGROUP)).err(), Some(Error::PointNotInSubgroup));
+        assert_eq!(g2_from_bytes(&g2_with_x(G2_NOT_IN_SUBGROUP)).err(), Some(Error::PointNotInSubgroup));
+    }
+
+    #[test]
+    fn unchecked_skips_only_the_subgroup_check() {
+        let outside = PublicKey::from_bytes_unchecked(&g2_with_x(G2_NOT_IN_SUBGROUP)).unwrap();
+        assert_eq!(outside.validate(), Err(Error::PointNotInSubgroup));
+
+        let pk = valid_pk();
+        assert_eq!(PublicKey::from_bytes_unchecked(&pk.to_bytes()).unwrap().validate(), Ok(()));
+
+        let identity = g2_to_bytes(&ECP2::new());
+        assert_eq!(PublicKey::from_bytes_unchecked(&identity).err(), Some(Error::IdentityPoint));
+        assert_eq!(PublicKey::from_bytes_unchecked(&g2_with_x(G2_OFF_CURVE)).err(), Some(Error::InvalidPointEncoding));
+        assert_eq!(PublicKey::from_bytes_unchecked(&pk.to_bytes()[1..]).err(), Some(Error::InvalidPointEncoding));
+    }
+}
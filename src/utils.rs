@@ -0,0 +1,90 @@
+//! Helpers shared by all schemes and by the benchmark binary.
+
+use mcore::bls12381::bls;
+use mcore::rand::RAND;
+use rand::{rngs::OsRng, RngCore};
+
+use crate::error::{Error, Result};
+use crate::types::{PublicKey, SecretKey};
+use crate::{BGS, G2S};
+
+/// Generates a BLS key pair from 32 bytes of key material drawn from `rng`.
+pub fn bls_setup(rng: &mut RAND) -> Result<(SecretKey, PublicKey)> {
+    let mut ikm: [u8; 32] = [0; 32];
+    let mut sk: [u8; BGS] = [0; BGS];
+    let mut pk: [u8; G2S] = [0; G2S];
+
+    for b in ikm.iter_mut() {
+        *b = rng.getbyte();
+    }
+
+    if bls::key_pair_generate(&ikm, &mut sk, &mut pk) != 0 {
+        return Err(Error::KeyGenerationFailed);
+    }
+
+    Ok((SecretKey::from_bytes(&sk)?, PublicKey::from_bytes(&pk)?))
+}
+
+pub fn fmt_time(time: &u128) -> String {
+    if (time % 1000000000000) / 1000000000 == 0 && (time % 1000000000) / 1000000 == 0 {
+        format!("{:3},{:03}", (time % 1000000) / 1000, time % 1000)
+    } else if (time % 1000000000000) / 1000000000 == 0 {
+        format!("{:3},{:03},{:03}", (time % 1000000000) / 1000000, (time % 1000000) / 1000, time % 1000)
+    } else {
+        format!("{:3},{:03},{:03},{:03}", (time % 1000000000000) / 1000000000, (time % 1000000000) / 1000000, (time % 1000000) / 1000, time % 1000)
+    }
+}
+
+/// Seeds a MIRACL `RAND` from the operating system's entropy source.
+pub fn gen_seed() -> RAND {
+    let mut raw: [u8; 100] = [0; 100];
+    let mut rng = RAND::new();
+    rng.clean();
+    OsRng.fill_bytes(&mut raw);
+    rng.seed(100, &raw);
+
+    rng
+}
+
+/// Encodes bytes as an uppercase hex string.
+pub fn tohex(array: &[u8]) -> String {
+    array.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// Decodes a hex string, ignoring surrounding whitespace and an optional `0x` prefix.
+pub fn fromhex(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim();
+    let s = s.strip_prefix("0x").unwrap_or(s);
+
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+//one chunk per thread, so every chunk pays its fixed cost once
+#[cfg(feature = "parallel")]
+pub(crate) fn chunk_len(n: usize) -> usize {
+    n.div_ceil(rayon::current_num_threads()).max(1)
+}
+
+/// Runs `f` on a pool of `threads` threads, so that `chunk_len` splits into that many
+/// chunks whatever the machine.
+#[cfg(all(test, feature = "parallel"))]
+pub(crate) fn with_threads<T: Send>(threads: usize, f: impl FnOnce() -> T + Send) -> T {
+    rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap().install(f)
+}
+
+/// A deterministic `RAND` for tests, so that failures reproduce.
+#[cfg(test)]
+pub(crate) fn test_rng(seed: u8) -> RAND {
+    let mut rng = RAND::new();
+    rng.clean();
+    rng.seed(32, &[seed; 32]);
+
+    rng
+}